@@ -0,0 +1,131 @@
+use crate::error::MxError;
+use crate::{MxSeries, TripStatus};
+use std::fmt;
+
+/// Highest output channel number on any MX Series model (MX100QP).
+pub(crate) const MAX_CHANNEL: u8 = 4;
+
+/// A validated output channel number.
+///
+/// Prefer this over bare `u8` channel arguments: `MxSeries::channel` binds it once, so a
+/// channel number can't be mixed up with another argument.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Channel(u8);
+
+impl Channel {
+    /// Creates a channel, checking that `n` is a valid MX Series output number.
+    pub fn new(n: u8) -> Result<Channel, MxError> {
+        if (1..=MAX_CHANNEL).contains(&n) {
+            Ok(Channel(n))
+        } else {
+            Err(MxError::InvalidParameter(format!("Channel must be 1-{}, got {}.", MAX_CHANNEL, n)))
+        }
+    }
+
+    /// Returns the channel number.
+    pub fn get(&self) -> u8 {
+        self.0
+    }
+}
+
+impl From<Channel> for u8 {
+    fn from(channel: Channel) -> u8 {
+        channel.0
+    }
+}
+
+impl fmt::Display for Channel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Typed access to a single output channel, returned by `MxSeries::channel`.
+///
+/// Each method is the equivalent of the `MxSeries` method of the same name with the channel
+/// argument already applied.
+pub struct ChannelHandle<'a> {
+    psu: &'a mut MxSeries,
+    channel: Channel,
+}
+
+impl<'a> ChannelHandle<'a> {
+    pub(crate) fn new(psu: &'a mut MxSeries, channel: Channel) -> Self {
+        ChannelHandle { psu, channel }
+    }
+
+    /// The channel this handle controls.
+    pub fn channel(&self) -> Channel {
+        self.channel
+    }
+
+    /// Get the output current of the channel.
+    pub fn get_current(&mut self) -> Result<f32, MxError> {
+        self.psu.get_current(self.channel.0)
+    }
+
+    /// Get the current limit of the channel.
+    pub fn get_current_limit(&mut self) -> Result<f32, MxError> {
+        self.psu.get_current_limit(self.channel.0)
+    }
+
+    /// Get the over-current protection trip point of the channel.
+    pub fn get_over_current_protection(&mut self) -> Result<Option<f32>, MxError> {
+        self.psu.get_over_current_protection(self.channel.0)
+    }
+
+    /// Get the over-voltage protection trip point of the channel.
+    pub fn get_over_voltage_protection(&mut self) -> Result<Option<f32>, MxError> {
+        self.psu.get_over_voltage_protection(self.channel.0)
+    }
+
+    /// Get the output voltage of the channel.
+    pub fn get_voltage(&mut self) -> Result<f32, MxError> {
+        self.psu.get_voltage(self.channel.0)
+    }
+
+    /// Get the set-point voltage of the channel.
+    pub fn get_voltage_setpoint(&mut self) -> Result<f32, MxError> {
+        self.psu.get_voltage_setpoint(self.channel.0)
+    }
+
+    /// Check if the channel is on or off.
+    pub fn is_output_on(&mut self) -> Result<bool, MxError> {
+        self.psu.is_output_on(self.channel.0)
+    }
+
+    /// Set the current limit of the channel.
+    pub fn set_current_limit(&mut self, value: f32) -> Result<(), MxError> {
+        self.psu.set_current_limit(self.channel.0, value)
+    }
+
+    /// Set the over-current protection trip point of the channel.
+    pub fn set_over_current_protection(&mut self, enable: bool, value: Option<f32>) -> Result<(), MxError> {
+        self.psu.set_over_current_protection(self.channel.0, enable, value)
+    }
+
+    /// Set the over-voltage protection trip point of the channel.
+    pub fn set_over_voltage_protection(&mut self, enable: bool, value: Option<f32>) -> Result<(), MxError> {
+        self.psu.set_over_voltage_protection(self.channel.0, enable, value)
+    }
+
+    /// Set the output voltage of the channel.
+    pub fn set_voltage(&mut self, value: f32, verify: bool) -> Result<(), MxError> {
+        self.psu.set_voltage(self.channel.0, value, verify)
+    }
+
+    /// Get the trip status of the channel.
+    pub fn trip_status(&mut self) -> Result<TripStatus, MxError> {
+        self.psu.channel_trip_status(self.channel.0)
+    }
+
+    /// Turn the channel off.
+    pub fn turn_off(&mut self) -> Result<(), MxError> {
+        self.psu.turn_off(self.channel.0)
+    }
+
+    /// Turn the channel on.
+    pub fn turn_on(&mut self) -> Result<(), MxError> {
+        self.psu.turn_on(self.channel.0)
+    }
+}
@@ -1,25 +1,170 @@
 use crate::error::MxError;
-use std::io::{BufRead, BufReader, Write};
-use std::net::TcpStream;
+use std::any::Any;
+use std::collections::VecDeque;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{Shutdown, TcpStream};
 use std::time::Duration;
 use std::sync::Mutex;
 
 const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+const DEFAULT_READ_TERMINATOR: u8 = b'\n';
+
+/// Where a reply ends, shared by the socket and serial connections.
+#[cfg(any(feature = "socket", feature = "serial"))]
+#[derive(Debug, Clone, Copy)]
+struct ReadTerminator {
+    byte: u8,
+    /// Whether `byte` is still to be detected from the replies.
+    detecting: bool,
+    /// Whether the last reply ended with a CR while detecting.
+    after_cr: bool,
+}
+
+#[cfg(any(feature = "socket", feature = "serial"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ReadStep {
+    Keep,
+    Skip,
+    End,
+}
+
+#[cfg(any(feature = "socket", feature = "serial"))]
+impl ReadTerminator {
+    fn fixed(byte: u8) -> Self {
+        ReadTerminator { byte, detecting: false, after_cr: false }
+    }
+
+    fn detect() -> Self {
+        ReadTerminator { byte: DEFAULT_READ_TERMINATOR, detecting: true, after_cr: false }
+    }
+
+    /// Classifies a received byte. `at_start` is whether nothing has been kept for the
+    /// current reply yet.
+    ///
+    /// While detecting, a reply ends at either CR or LF. An LF straight after a CR-terminated
+    /// reply means CRLF, and anything else means CR only; a reply ending in a lone LF means LF.
+    fn step(&mut self, byte: u8, at_start: bool) -> ReadStep {
+        if !self.detecting {
+            return if byte == self.byte { ReadStep::End } else { ReadStep::Keep };
+        }
+        match byte {
+            b'\n' if at_start && self.after_cr => {
+                *self = ReadTerminator::fixed(b'\n');
+                ReadStep::Skip
+            }
+            b'\n' | b'\r' if at_start => ReadStep::Skip,
+            b'\n' => {
+                *self = ReadTerminator::fixed(b'\n');
+                ReadStep::End
+            }
+            b'\r' => {
+                self.after_cr = true;
+                ReadStep::End
+            }
+            _ => {
+                if self.after_cr {
+                    *self = ReadTerminator::fixed(b'\r');
+                }
+                ReadStep::Keep
+            }
+        }
+    }
+}
+
+/// The transport behind a `Connection`, for code that only sees `dyn Connection`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ConnectionKind {
+    Socket,
+    Serial,
+    Mock,
+    /// A connection implemented outside this crate.
+    Other,
+}
 
 pub trait Connection: Send + Sync {
     fn write_command(&mut self, command: &str) -> Result<(), MxError>;
+    /// Writes exactly the given bytes, without appending a line terminator.
+    ///
+    /// By default, bytes ending in `\n` are passed to `write_command` without it; anything
+    /// else fails with `MxError::UnsupportedFeature`, as `write_command` always appends the
+    /// terminator.
+    fn write_command_raw(&mut self, bytes: &[u8]) -> Result<(), MxError> {
+        match bytes.strip_suffix(b"\n").map(std::str::from_utf8) {
+            Some(Ok(command)) => self.write_command(command),
+            _ => Err(MxError::UnsupportedFeature("Raw writes are not supported on this connection".to_string())),
+        }
+    }
     fn read_response(&mut self) -> Result<String, MxError>;
+    /// Reads `n` reply lines, e.g. the replies to a batch of queries separated by `;`.
+    fn read_lines(&mut self, n: usize) -> Result<Vec<String>, MxError> {
+        (0..n).map(|_| self.read_response()).collect()
+    }
     fn query(&mut self, command: &str) -> Result<String, MxError> {
         self.write_command(command)?;
         self.read_response()
     }
     fn set_timeout(&mut self, duration: Duration) -> Result<(), MxError>;
+    /// Returns the connection as `Any`, so it can be downcast to its concrete type, or `None`
+    /// if the connection does not support downcasting, which is the default.
+    fn as_any_mut(&mut self) -> Option<&mut dyn Any> {
+        None
+    }
+    /// Flushes and shuts down the connection. The underlying resource is released on drop.
+    fn close(&mut self) -> Result<(), MxError> {
+        Ok(())
+    }
+    /// Sets the byte that ends a reply, `\n` by default.
+    ///
+    /// By default only `\n` is accepted, which needs no change; other terminators fail with
+    /// `MxError::UnsupportedFeature`.
+    fn set_read_terminator(&mut self, terminator: u8) -> Result<(), MxError> {
+        if terminator == DEFAULT_READ_TERMINATOR {
+            Ok(())
+        } else {
+            Err(MxError::UnsupportedFeature("Read terminator cannot be changed on this connection".to_string()))
+        }
+    }
+    /// Sets a delay inserted between each character written.
+    ///
+    /// Some cheap or opto-isolated USB-to-RS-232 adapters drop characters when a command is
+    /// written in one go. A non-zero delay writes commands character-by-character instead.
+    /// Only serial connections support this.
+    fn set_write_char_delay(&mut self, _delay: Duration) -> Result<(), MxError> {
+        Err(MxError::UnsupportedFeature("Write character delay is only supported on serial connections".to_string()))
+    }
+    /// Detects whether replies end with LF, CRLF or CR from the replies themselves, instead
+    /// of using the terminator set by `set_read_terminator`.
+    ///
+    /// Detection finishes with the first reply that shows the convention unambiguously; until
+    /// then a reply ends at either CR or LF.
+    fn detect_read_terminator(&mut self) -> Result<(), MxError> {
+        Err(MxError::UnsupportedFeature("Read terminator detection is not supported on this connection".to_string()))
+    }
+    /// The transport this connection uses.
+    fn connection_kind(&self) -> ConnectionKind {
+        ConnectionKind::Other
+    }
+    /// Enables keepalive probes after the connection has been idle for `interval`.
+    ///
+    /// Only socket connections support this.
+    fn set_keepalive(&mut self, _interval: Duration) -> Result<(), MxError> {
+        Err(MxError::UnsupportedFeature("Keepalive is only supported on socket connections".to_string()))
+    }
+    /// Discards any input received but not read yet, such as a late reply to a query that
+    /// timed out. Does nothing by default.
+    fn clear_input(&mut self) -> Result<(), MxError> {
+        Ok(())
+    }
 }
 
 #[cfg(feature = "socket")]
 pub struct SocketConnection {
     stream: TcpStream,
     reader: BufReader<TcpStream>,
+    read_terminator: ReadTerminator,
+    /// The start of a reply whose read timed out, completed by the next read.
+    partial: Vec<u8>,
 }
 
 #[cfg(feature = "socket")]
@@ -32,23 +177,49 @@ impl SocketConnection {
         Ok(SocketConnection {
             stream,
             reader: BufReader::new(reader_stream),
+            read_terminator: ReadTerminator::fixed(DEFAULT_READ_TERMINATOR),
+            partial: Vec::new(),
         })
     }
+
+    /// The underlying TCP stream, for setting socket options.
+    pub fn stream(&self) -> &TcpStream {
+        &self.stream
+    }
 }
 
 #[cfg(feature = "socket")]
 impl Connection for SocketConnection {
     fn write_command(&mut self, command: &str) -> Result<(), MxError> {
         let full_command = format!("{}\n", command);
-        self.stream.write_all(full_command.as_bytes())?;
+        self.write_command_raw(full_command.as_bytes())
+    }
+
+    fn write_command_raw(&mut self, bytes: &[u8]) -> Result<(), MxError> {
+        self.stream.write_all(bytes)?;
         self.stream.flush()?;
         Ok(())
     }
 
     fn read_response(&mut self) -> Result<String, MxError> {
-        let mut response = String::new();
-        self.reader.read_line(&mut response)?;
-        Ok(response.trim().to_string())
+        // Bytes read before a timeout are kept in `partial`, so that reading again returns the
+        // whole reply rather than its tail.
+        if self.read_terminator.detecting {
+            let mut byte = [0u8; 1];
+            while self.reader.read(&mut byte)? == 1 {
+                match self.read_terminator.step(byte[0], self.partial.is_empty()) {
+                    ReadStep::Keep => self.partial.push(byte[0]),
+                    ReadStep::Skip => {}
+                    ReadStep::End => break,
+                }
+            }
+        } else {
+            self.reader.read_until(self.read_terminator.byte, &mut self.partial)?;
+        }
+        let response = std::mem::take(&mut self.partial);
+        String::from_utf8(response)
+            .map(|s| s.trim().to_string())
+            .map_err(|e| MxError::Parse(format!("Invalid UTF-8 sequence: {}", e)))
     }
 
     fn set_timeout(&mut self, duration: Duration) -> Result<(), MxError> {
@@ -56,11 +227,82 @@ impl Connection for SocketConnection {
         self.stream.set_write_timeout(Some(duration))?;
         Ok(())
     }
+
+    fn set_read_terminator(&mut self, terminator: u8) -> Result<(), MxError> {
+        self.read_terminator = ReadTerminator::fixed(terminator);
+        Ok(())
+    }
+
+    fn detect_read_terminator(&mut self) -> Result<(), MxError> {
+        self.read_terminator = ReadTerminator::detect();
+        Ok(())
+    }
+
+    fn as_any_mut(&mut self) -> Option<&mut dyn Any> {
+        Some(self)
+    }
+
+    fn connection_kind(&self) -> ConnectionKind {
+        ConnectionKind::Socket
+    }
+
+    fn close(&mut self) -> Result<(), MxError> {
+        self.stream.flush()?;
+        self.stream.shutdown(Shutdown::Both)?;
+        Ok(())
+    }
+
+    fn set_keepalive(&mut self, interval: Duration) -> Result<(), MxError> {
+        let keepalive = socket2::TcpKeepalive::new().with_time(interval).with_interval(interval);
+        socket2::SockRef::from(&self.stream).set_tcp_keepalive(&keepalive)?;
+        Ok(())
+    }
+
+    fn clear_input(&mut self) -> Result<(), MxError> {
+        self.partial.clear();
+        let buffered = self.reader.buffer().len();
+        self.reader.consume(buffered);
+        // The reader shares the socket with `stream`, so this makes both non-blocking.
+        self.stream.set_nonblocking(true)?;
+        let mut discard = [0u8; 256];
+        let drained = loop {
+            match self.reader.get_mut().read(&mut discard) {
+                Ok(0) => break Ok(()),
+                Ok(_) => {}
+                Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => break Ok(()),
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+                Err(e) => break Err(e),
+            }
+        };
+        self.stream.set_nonblocking(false)?;
+        Ok(drained?)
+    }
+}
+
+/// Raw byte access for custom framing. Reads share the buffer used by `read_response`.
+#[cfg(feature = "socket")]
+impl Read for SocketConnection {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.reader.read(buf)
+    }
+}
+
+#[cfg(feature = "socket")]
+impl Write for SocketConnection {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.stream.write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.stream.flush()
+    }
 }
 
 #[cfg(feature = "serial")]
 pub struct SerialConnection {
     port: Mutex<Box<dyn serialport::SerialPort>>,
+    write_char_delay: Duration,
+    read_terminator: ReadTerminator,
 }
 
 #[cfg(feature = "serial")]
@@ -69,7 +311,17 @@ impl SerialConnection {
         let port = serialport::new(port_name, baud_rate)
             .timeout(DEFAULT_TIMEOUT)
             .open()?;
-        Ok(SerialConnection { port: Mutex::new(port) })
+        Ok(SerialConnection {
+            port: Mutex::new(port),
+            write_char_delay: Duration::ZERO,
+            read_terminator: ReadTerminator::fixed(DEFAULT_READ_TERMINATOR),
+        })
+    }
+
+    /// The underlying serial port, for settings not otherwise exposed.
+    pub fn port_mut(&mut self) -> Result<&mut dyn serialport::SerialPort, MxError> {
+        let port = self.port.get_mut().map_err(|_e| MxError::Io(std::io::Error::other("Serial port mutex poisoned")))?;
+        Ok(port.as_mut())
     }
 }
 
@@ -77,8 +329,20 @@ impl SerialConnection {
 impl Connection for SerialConnection {
     fn write_command(&mut self, command: &str) -> Result<(), MxError> {
         let full_command = format!("{}\n", command);
+        self.write_command_raw(full_command.as_bytes())
+    }
+
+    fn write_command_raw(&mut self, bytes: &[u8]) -> Result<(), MxError> {
         let mut port_guard = self.port.lock().map_err(|_e| MxError::Io(std::io::Error::new(std::io::ErrorKind::Other, "Serial port mutex poisoned")))?;
-        port_guard.write_all(full_command.as_bytes())?;
+        if self.write_char_delay.is_zero() {
+            port_guard.write_all(bytes)?;
+        } else {
+            for byte in bytes {
+                port_guard.write_all(std::slice::from_ref(byte))?;
+                port_guard.flush()?;
+                std::thread::sleep(self.write_char_delay);
+            }
+        }
         port_guard.flush()?;
         Ok(())
     }
@@ -93,14 +357,11 @@ impl Connection for SerialConnection {
                     // End of stream or timeout if no bytes were read.
                     break;
                 }
-                Ok(1) => {
-                    if byte_buf[0] == b'\n' {
-                        break;
-                    }
-                    if byte_buf[0] != b'\r' { // Ignore CR
-                        serial_buf.push(byte_buf[0]);
-                    }
-                }
+                Ok(1) => match self.read_terminator.step(byte_buf[0], serial_buf.is_empty()) {
+                    ReadStep::End => break,
+                    ReadStep::Keep if byte_buf[0] != b'\r' => serial_buf.push(byte_buf[0]), // Ignore CR
+                    _ => {}
+                },
                 Ok(_) => {
                     // This case should ideally not be reached if reading into a 1-byte buffer.
                     // If it is, it implies more than 1 byte was read into a 1-byte buffer,
@@ -124,4 +385,328 @@ impl Connection for SerialConnection {
         port_guard.set_timeout(duration)?;
         Ok(())
     }
+
+    fn set_read_terminator(&mut self, terminator: u8) -> Result<(), MxError> {
+        self.read_terminator = ReadTerminator::fixed(terminator);
+        Ok(())
+    }
+
+    fn detect_read_terminator(&mut self) -> Result<(), MxError> {
+        self.read_terminator = ReadTerminator::detect();
+        Ok(())
+    }
+
+    fn set_write_char_delay(&mut self, delay: Duration) -> Result<(), MxError> {
+        self.write_char_delay = delay;
+        Ok(())
+    }
+
+    fn as_any_mut(&mut self) -> Option<&mut dyn Any> {
+        Some(self)
+    }
+
+    fn connection_kind(&self) -> ConnectionKind {
+        ConnectionKind::Serial
+    }
+
+    fn close(&mut self) -> Result<(), MxError> {
+        let mut port_guard = self.port.lock().map_err(|_e| MxError::Io(std::io::Error::other("Serial port mutex poisoned")))?;
+        port_guard.flush()?;
+        Ok(())
+    }
+
+    fn clear_input(&mut self) -> Result<(), MxError> {
+        let port_guard = self.port.lock().map_err(|_e| MxError::Io(std::io::Error::other("Serial port mutex poisoned")))?;
+        port_guard.clear(serialport::ClearBuffer::Input)?;
+        Ok(())
+    }
+}
+
+/// Raw byte access for custom framing. Writes ignore the write character delay.
+#[cfg(feature = "serial")]
+impl Read for SerialConnection {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let port = self.port.get_mut().map_err(|_e| std::io::Error::other("Serial port mutex poisoned"))?;
+        port.read(buf)
+    }
+}
+
+#[cfg(feature = "serial")]
+impl Write for SerialConnection {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let port = self.port.get_mut().map_err(|_e| std::io::Error::other("Serial port mutex poisoned"))?;
+        port.write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        let port = self.port.get_mut().map_err(|_e| std::io::Error::other("Serial port mutex poisoned"))?;
+        port.flush()
+    }
+}
+
+/// An in-memory connection for testing code that uses `MxSeries` without an instrument.
+///
+/// Replies are queued up front and returned in order; every write is recorded exactly as it
+/// would go on the wire, including the line terminator.
+#[derive(Debug, Default)]
+pub struct MockConnection {
+    replies: VecDeque<String>,
+    written: Vec<Vec<u8>>,
+}
+
+impl MockConnection {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queues a reply to be returned by a later read.
+    pub fn push_reply(&mut self, reply: &str) {
+        self.replies.push_back(reply.to_string());
+    }
+
+    /// Every write so far, oldest first, as raw bytes.
+    pub fn written_bytes(&self) -> &[Vec<u8>] {
+        &self.written
+    }
+}
+
+impl Connection for MockConnection {
+    fn write_command(&mut self, command: &str) -> Result<(), MxError> {
+        let full_command = format!("{}\n", command);
+        self.write_command_raw(full_command.as_bytes())
+    }
+
+    fn write_command_raw(&mut self, bytes: &[u8]) -> Result<(), MxError> {
+        self.written.push(bytes.to_vec());
+        Ok(())
+    }
+
+    fn read_response(&mut self) -> Result<String, MxError> {
+        self.replies.pop_front().ok_or_else(|| {
+            MxError::Io(std::io::Error::new(std::io::ErrorKind::TimedOut, "No reply queued on mock connection"))
+        })
+    }
+
+    fn set_timeout(&mut self, _duration: Duration) -> Result<(), MxError> {
+        Ok(())
+    }
+
+    fn as_any_mut(&mut self) -> Option<&mut dyn Any> {
+        Some(self)
+    }
+
+    fn connection_kind(&self) -> ConnectionKind {
+        ConnectionKind::Mock
+    }
+
+    fn set_read_terminator(&mut self, _terminator: u8) -> Result<(), MxError> {
+        Ok(())
+    }
+}
+
+/// Wraps a connection and logs every command, reply and error at `debug` level with `tracing`,
+/// e.g. `LoggingConnection::new(SocketConnection::new(address)?)`.
+///
+/// Connection wrappers stack, e.g. `RetryingConnection::new(LoggingConnection::new(..), 2)`
+/// logs each attempt. Downcasting with `MxSeries::connection_downcast_mut` reaches the
+/// wrapped connection.
+#[cfg(feature = "tracing")]
+pub struct LoggingConnection<C> {
+    inner: C,
+}
+
+#[cfg(feature = "tracing")]
+impl<C: Connection> LoggingConnection<C> {
+    pub fn new(inner: C) -> Self {
+        LoggingConnection { inner }
+    }
+
+    /// The wrapped connection.
+    pub fn inner_mut(&mut self) -> &mut C {
+        &mut self.inner
+    }
+
+    /// Returns the wrapped connection.
+    pub fn into_inner(self) -> C {
+        self.inner
+    }
+
+    fn log<T: std::fmt::Debug>(call: &str, arg: &dyn std::fmt::Debug, result: &Result<T, MxError>) {
+        match result {
+            Ok(value) => tracing::debug!(call, ?arg, ?value, "connection call succeeded"),
+            Err(e) => tracing::debug!(call, ?arg, error = %e, "connection call failed"),
+        }
+    }
+}
+
+#[cfg(feature = "tracing")]
+impl<C: Connection + 'static> Connection for LoggingConnection<C> {
+    fn write_command(&mut self, command: &str) -> Result<(), MxError> {
+        let result = self.inner.write_command(command);
+        Self::log("write_command", &command, &result);
+        result
+    }
+
+    fn write_command_raw(&mut self, bytes: &[u8]) -> Result<(), MxError> {
+        let result = self.inner.write_command_raw(bytes);
+        Self::log("write_command_raw", &String::from_utf8_lossy(bytes), &result);
+        result
+    }
+
+    fn read_response(&mut self) -> Result<String, MxError> {
+        let result = self.inner.read_response();
+        Self::log("read_response", &(), &result);
+        result
+    }
+
+    fn read_lines(&mut self, n: usize) -> Result<Vec<String>, MxError> {
+        let result = self.inner.read_lines(n);
+        Self::log("read_lines", &n, &result);
+        result
+    }
+
+    fn set_timeout(&mut self, duration: Duration) -> Result<(), MxError> {
+        let result = self.inner.set_timeout(duration);
+        Self::log("set_timeout", &duration, &result);
+        result
+    }
+
+    fn as_any_mut(&mut self) -> Option<&mut dyn Any> {
+        self.inner.as_any_mut()
+    }
+
+    fn close(&mut self) -> Result<(), MxError> {
+        let result = self.inner.close();
+        Self::log("close", &(), &result);
+        result
+    }
+
+    fn set_read_terminator(&mut self, terminator: u8) -> Result<(), MxError> {
+        let result = self.inner.set_read_terminator(terminator);
+        Self::log("set_read_terminator", &terminator, &result);
+        result
+    }
+
+    fn set_write_char_delay(&mut self, delay: Duration) -> Result<(), MxError> {
+        let result = self.inner.set_write_char_delay(delay);
+        Self::log("set_write_char_delay", &delay, &result);
+        result
+    }
+
+    fn detect_read_terminator(&mut self) -> Result<(), MxError> {
+        let result = self.inner.detect_read_terminator();
+        Self::log("detect_read_terminator", &(), &result);
+        result
+    }
+
+    fn connection_kind(&self) -> ConnectionKind {
+        self.inner.connection_kind()
+    }
+
+    fn set_keepalive(&mut self, interval: Duration) -> Result<(), MxError> {
+        let result = self.inner.set_keepalive(interval);
+        Self::log("set_keepalive", &interval, &result);
+        result
+    }
+
+    fn clear_input(&mut self) -> Result<(), MxError> {
+        let result = self.inner.clear_input();
+        Self::log("clear_input", &(), &result);
+        result
+    }
+}
+
+/// Wraps a connection and reads again, up to `retries` more times, when a read fails with a
+/// transient I/O error (timed out, would block or interrupted) or returns an empty reply, as a
+/// serial read does on timeout.
+///
+/// Unlike `MxSeries::set_retry_policy`, which resends a whole command after its status
+/// check, only reads are repeated, so a late reply is read rather than the command being
+/// sent twice. Writes are not retried, as part of the command may already have been sent.
+/// Downcasting with `MxSeries::connection_downcast_mut` reaches the wrapped connection.
+pub struct RetryingConnection<C> {
+    inner: C,
+    retries: u32,
+}
+
+impl<C: Connection> RetryingConnection<C> {
+    pub fn new(inner: C, retries: u32) -> Self {
+        RetryingConnection { inner, retries }
+    }
+
+    /// The wrapped connection.
+    pub fn inner_mut(&mut self) -> &mut C {
+        &mut self.inner
+    }
+
+    /// Returns the wrapped connection.
+    pub fn into_inner(self) -> C {
+        self.inner
+    }
+
+    fn read_with_retries(&mut self) -> Result<String, MxError> {
+        let mut attempt = 0;
+        loop {
+            match self.inner.read_response() {
+                Err(MxError::Io(e)) if attempt < self.retries && Self::is_transient(&e) => attempt += 1,
+                Ok(reply) if attempt < self.retries && reply.is_empty() => attempt += 1,
+                result => return result,
+            }
+        }
+    }
+
+    fn is_transient(error: &std::io::Error) -> bool {
+        use std::io::ErrorKind;
+        matches!(error.kind(), ErrorKind::TimedOut | ErrorKind::WouldBlock | ErrorKind::Interrupted)
+    }
+}
+
+impl<C: Connection + 'static> Connection for RetryingConnection<C> {
+    fn write_command(&mut self, command: &str) -> Result<(), MxError> {
+        self.inner.write_command(command)
+    }
+
+    fn write_command_raw(&mut self, bytes: &[u8]) -> Result<(), MxError> {
+        self.inner.write_command_raw(bytes)
+    }
+
+    fn read_response(&mut self) -> Result<String, MxError> {
+        self.read_with_retries()
+    }
+
+    fn set_timeout(&mut self, duration: Duration) -> Result<(), MxError> {
+        self.inner.set_timeout(duration)
+    }
+
+    fn as_any_mut(&mut self) -> Option<&mut dyn Any> {
+        self.inner.as_any_mut()
+    }
+
+    fn close(&mut self) -> Result<(), MxError> {
+        self.inner.close()
+    }
+
+    fn set_read_terminator(&mut self, terminator: u8) -> Result<(), MxError> {
+        self.inner.set_read_terminator(terminator)
+    }
+
+    fn set_write_char_delay(&mut self, delay: Duration) -> Result<(), MxError> {
+        self.inner.set_write_char_delay(delay)
+    }
+
+    fn detect_read_terminator(&mut self) -> Result<(), MxError> {
+        self.inner.detect_read_terminator()
+    }
+
+    fn connection_kind(&self) -> ConnectionKind {
+        self.inner.connection_kind()
+    }
+
+    fn set_keepalive(&mut self, interval: Duration) -> Result<(), MxError> {
+        self.inner.set_keepalive(interval)
+    }
+
+    fn clear_input(&mut self) -> Result<(), MxError> {
+        self.inner.clear_input()
+    }
 }
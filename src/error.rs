@@ -31,6 +31,39 @@ pub enum MxError {
     #[error("Undefined error code {0} from device. Command was: {1}")]
     UndefinedDeviceErrorCode(i32, String),
 
+    #[error("Trip condition on channel {channel} persists after reset")]
+    TripPersists { channel: u8 },
+
+    #[error("Read-back of {setting} does not match: expected {expected}, got {actual}")]
+    ConfirmationMismatch {
+        setting: String,
+        expected: String,
+        actual: String,
+    },
+
+    #[error("{setting} protection was enabled but its trip point was not applied: {source}")]
+    ProtectionValueRejected {
+        setting: String,
+        #[source]
+        source: Box<MxError>,
+    },
+
+    #[error("Multi-on/off options of channel {channel} were not applied: {source}")]
+    MultiOp {
+        channel: u8,
+        #[source]
+        source: Box<MxError>,
+    },
+
+    #[error("Configuration did not verify and was rolled back: {}", .mismatches.join("; "))]
+    ConfigurationRolledBack { mismatches: Vec<String> },
+
+    #[error("Timed out: {0}")]
+    Timeout(String),
+
+    #[error("Operation cancelled")]
+    Cancelled,
+
     #[error("Connection not established or invalid")]
     NotConnected,
 
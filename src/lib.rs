@@ -1,12 +1,36 @@
+pub mod channel;
 pub mod connection;
 pub mod error;
+pub mod parse;
+pub mod queue;
 
-use connection::Connection;
+use channel::{Channel, ChannelHandle};
+use connection::{Connection, ConnectionKind};
 use error::MxError;
 use phf::phf_map;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
+
+/// Delay after writing a command, giving the instrument time to update its status registers.
+const COMMAND_DELAY: Duration = Duration::from_millis(50);
+
+/// Default largest difference between a written and read-back value that is still considered a
+/// match, see `MxSeries::set_verify_epsilon`.
+const VERIFY_TOLERANCE: f32 = 0.01;
+
+/// Whether `a` and `b` differ by no more than `eps`.
+fn approx_eq(a: f32, b: f32, eps: f32) -> bool {
+    (a - b).abs() <= eps
+}
+
+// How long `reset` waits for the instrument to complete `*RST` unless overridden.
+const DEFAULT_RESET_TIMEOUT: Duration = Duration::from_secs(10);
 
 static EXECUTION_ERROR_CODES: phf::Map<i32, (&'static str, &'static str)> = phf_map! {
     0i32 => ("OK", "No error has occurred since this register was last read."),
@@ -17,20 +41,340 @@ static EXECUTION_ERROR_CODES: phf::Map<i32, (&'static str, &'static str)> = phf_
     200i32 => ("AccessDenied", "An attempt was made to change the instrument's settings from an interface which is locked out of write privileges by a lock held by another interface.")
 };
 
+// (max voltage, max current) of each voltage range, in VRANGE index order starting at 1.
+const MX100_RANGES: &[(f32, f32)] = &[(16.0, 6.0), (35.0, 3.0), (70.0, 1.5)];
+const MX180_OUTPUT1_RANGES: &[(f32, f32)] = &[(15.0, 20.0), (30.0, 10.0), (60.0, 6.0), (120.0, 3.0)];
+const MX180_OUTPUT2_RANGES: &[(f32, f32)] = &[(15.0, 10.0), (30.0, 6.0), (60.0, 3.0)];
+const MX180_OUTPUT3_RANGES: &[(f32, f32)] = &[(5.5, 3.0), (12.0, 1.5)];
+
+// Significant digits of the voltage and current meters.
+const METER_DIGITS: i32 = 5;
+
+// Setpoints are sent with three decimals.
+const SETPOINT_STEP: f32 = 0.001;
+
+/// Voltage ranges of each output channel, keyed by the model reported by `*IDN?`.
+static MODEL_RANGES: phf::Map<&'static str, &'static [&'static [(f32, f32)]]> = phf_map! {
+    "MX100T" => &[MX100_RANGES, MX100_RANGES, MX100_RANGES],
+    "MX100TP" => &[MX100_RANGES, MX100_RANGES, MX100_RANGES],
+    "MX100Q" => &[MX100_RANGES, MX100_RANGES, MX100_RANGES, MX100_RANGES],
+    "MX100QP" => &[MX100_RANGES, MX100_RANGES, MX100_RANGES, MX100_RANGES],
+    "MX180T" => &[MX180_OUTPUT1_RANGES, MX180_OUTPUT2_RANGES, MX180_OUTPUT3_RANGES],
+    "MX180TP" => &[MX180_OUTPUT1_RANGES, MX180_OUTPUT2_RANGES, MX180_OUTPUT3_RANGES],
+};
+
 /// Represents the state of the Event Status Register.
 pub enum ESRValue {
     Integer(u8),
     BinaryString(String),
 }
 
+/// Identity of the instrument as reported by `*IDN?`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct DeviceIdentity {
+    pub manufacturer: String,
+    pub model: String,
+    pub serial: String,
+    pub firmware: String,
+}
+
+/// Optional capabilities that vary between instruments, for use with `MxSeries::supports`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Feature {
+    /// Programmable sequences of setpoints.
+    ListMode,
+    /// Remote sense terminals compensating for lead resistance.
+    RemoteSense,
+    /// Programmable output slew rate.
+    SlewLimiting,
+    /// LAN interface.
+    Lan,
+}
+
+/// LAN settings of the instrument.
+///
+/// There is no gateway or MAC address field: the MX Series remote command set has no query
+/// for either, they are only shown on the instrument's web page.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NetworkConfig {
+    pub ip_address: String,
+    pub netmask: String,
+    /// Address assignment mode as reported by `NETCONFIG?`, e.g. "DHCP", "AUTO" or "STATIC".
+    /// Included because it tells whether `ip_address` and `netmask` were assigned by DHCP
+    /// and may change.
+    pub mode: String,
+}
+
+/// Trip conditions of an output channel, decoded from its limit status register.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct TripStatus {
+    pub over_voltage: bool,
+    pub over_current: bool,
+    pub hardware: bool,
+}
+
+impl TripStatus {
+    /// Returns `true` if any trip condition is present.
+    pub fn is_tripped(&self) -> bool {
+        self.over_voltage || self.over_current || self.hardware
+    }
+}
+
+/// Regulation mode of an output channel, decoded from its limit status register.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OperatingMode {
+    ConstantVoltage,
+    ConstantCurrent,
+    /// Neither limit is active, e.g. because the output is off.
+    Unregulated,
+}
+
+impl fmt::Display for OperatingMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            OperatingMode::ConstantVoltage => "CV",
+            OperatingMode::ConstantCurrent => "CC",
+            OperatingMode::Unregulated => "UR",
+        })
+    }
+}
+
+/// Why an output channel is on or off, as returned by `MxSeries::output_off_reason`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OffReason {
+    On,
+    /// Off with no trip condition present, e.g. turned off deliberately.
+    ManualOff,
+    /// Off with a trip condition present.
+    TrippedOff,
+}
+
+/// Measured output of a channel.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ChannelMeasurement {
+    pub channel: u8,
+    pub voltage: f32,
+    pub current: f32,
+}
+
+impl fmt::Display for ChannelMeasurement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "CH{}: {:.3}V / {:.3}A", self.channel, self.voltage, self.current)
+    }
+}
+
+/// Measured output of a channel together with its output and regulation state.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChannelState {
+    pub channel: u8,
+    pub voltage: f32,
+    pub current: f32,
+    pub output_on: bool,
+    pub mode: OperatingMode,
+}
+
+impl fmt::Display for ChannelState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "CH{}: {:.3}V / {:.3}A [{}, {}]",
+            self.channel,
+            self.voltage,
+            self.current,
+            if self.output_on { "ON" } else { "OFF" },
+            self.mode
+        )
+    }
+}
+
+/// Trips observed on a channel by `MxSeries::channel_trip_status`.
+#[derive(Debug, Clone, Copy, Default)]
+struct TripHistory {
+    last: TripStatus,
+    ovp_trips: u32,
+    ocp_trips: u32,
+    /// When a new trip of any kind was last observed.
+    last_trip: Option<Instant>,
+}
+
+/// Setpoints, measurements and status flags of a channel, read in one go.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChannelStatusLine {
+    pub channel: u8,
+    pub voltage_setpoint: f32,
+    pub voltage: f32,
+    pub current_limit: f32,
+    pub current: f32,
+    pub mode: OperatingMode,
+    pub trip: TripStatus,
+}
+
+/// Setpoints and protection trip points of a channel, as held in a store.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChannelConfig {
+    pub voltage: f32,
+    pub current_limit: f32,
+    /// `None` if over-voltage protection is disabled.
+    pub over_voltage_protection: Option<f32>,
+    /// `None` if over-current protection is disabled.
+    pub over_current_protection: Option<f32>,
+}
+
+impl ChannelConfig {
+    fn matches(&self, other: &ChannelConfig, eps: f32) -> bool {
+        self.mismatches(other, eps).is_empty()
+    }
+
+    /// Describes each setting of `actual` that differs from `self` by more than `tolerance`.
+    fn mismatches(&self, actual: &ChannelConfig, tolerance: f32) -> Vec<String> {
+        let show = |value: Option<f32>| value.map_or("OFF".to_string(), |v| format!("{:.3}", v));
+        let fields = [
+            ("voltage", Some(self.voltage), Some(actual.voltage)),
+            ("current limit", Some(self.current_limit), Some(actual.current_limit)),
+            ("OVP", self.over_voltage_protection, actual.over_voltage_protection),
+            ("OCP", self.over_current_protection, actual.over_current_protection),
+        ];
+        fields.iter()
+            .filter(|(_, expected, actual)| match (expected, actual) {
+                (Some(e), Some(a)) => !approx_eq(*e, *a, tolerance),
+                (None, None) => false,
+                _ => true,
+            })
+            .map(|(name, expected, actual)| format!("{}: expected {}, got {}", name, show(*expected), show(*actual)))
+            .collect()
+    }
+}
+
+/// Limits of a voltage range, as returned by `MxSeries::describe_range`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RangeInfo {
+    /// The `VRANGE` index, starting at 1.
+    pub index: i32,
+    pub max_voltage: f32,
+    pub max_current: f32,
+}
+
+/// Commanded settings of a channel, as returned by `MxSeries::get_setpoints`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ChannelSetpoints {
+    pub channel: u8,
+    pub voltage: f32,
+    pub current_limit: f32,
+    /// `None` if over-voltage protection is disabled.
+    pub over_voltage_protection: Option<f32>,
+    /// `None` if over-current protection is disabled.
+    pub over_current_protection: Option<f32>,
+    /// Voltage range index, as used by `set_voltage_range`.
+    pub range: i32,
+}
+
+/// Instrument state collected by `MxSeries::diagnostics_dump` or `MxSeries::diagnostics`
+/// for bug reports.
+///
+/// `Display` renders it as a block of text suitable for pasting into an issue. With the
+/// `serde` feature it can also be serialized, e.g. to attach as JSON.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct DiagnosticsReport {
+    pub identity: DeviceIdentity,
+    pub event_status_register: u8,
+    pub status_byte: u8,
+    /// `(channel, limit status register)` of each channel.
+    pub limit_status: Vec<(u8, u8)>,
+    /// `(channel, trip status)` of each channel, decoded from `limit_status`.
+    pub trips: Vec<(u8, TripStatus)>,
+    /// `(code, type, description)` of each pending execution error, as returned by
+    /// `MxSeries::drain_errors`. Only `MxSeries::diagnostics` reads these.
+    pub execution_errors: Vec<(i32, String, String)>,
+    pub setpoints: Vec<ChannelSetpoints>,
+    pub measurements: Vec<ChannelMeasurement>,
+}
+
+impl fmt::Display for DiagnosticsReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let id = &self.identity;
+        writeln!(f, "{} {} (serial {}, firmware {})", id.manufacturer, id.model, id.serial, id.firmware)?;
+        writeln!(f, "ESR {:08b}, STB {:08b}", self.event_status_register, self.status_byte)?;
+        let limit = |value: Option<f32>, unit: &str| value.map_or("off".to_string(), |v| format!("{:.3}{}", v, unit));
+        for setpoint in &self.setpoints {
+            write!(
+                f,
+                "CH{}: set {:.3}V / {:.3}A (range {}), OVP {}, OCP {}",
+                setpoint.channel,
+                setpoint.voltage,
+                setpoint.current_limit,
+                setpoint.range,
+                limit(setpoint.over_voltage_protection, "V"),
+                limit(setpoint.over_current_protection, "A"),
+            )?;
+            if let Some(m) = self.measurements.iter().find(|m| m.channel == setpoint.channel) {
+                write!(f, ", measured {:.3}V / {:.3}A", m.voltage, m.current)?;
+            }
+            if let Some((_, lsr)) = self.limit_status.iter().find(|(channel, _)| *channel == setpoint.channel) {
+                write!(f, ", LSR {:08b}", lsr)?;
+            }
+            if let Some((_, trip)) = self.trips.iter().find(|(channel, trip)| *channel == setpoint.channel && trip.is_tripped()) {
+                write!(f, ", TRIPPED {:?}", trip)?;
+            }
+            writeln!(f)?;
+        }
+        for (code, error_type, description) in &self.execution_errors {
+            writeln!(f, "EER {}: {} - {}", code, error_type, description)?;
+        }
+        Ok(())
+    }
+}
+
+/// A complete instrument configuration, for use with `MxSeries::apply_profile`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct InstrumentProfile {
+    /// Voltage tracking mode as used by `set_voltage_tracking_mode`, or `None` to leave it.
+    pub tracking_mode: Option<i32>,
+    /// Settings of each channel to configure; channels not listed are left unchanged.
+    pub channels: BTreeMap<u8, ChannelConfig>,
+    /// Read every setting back after applying the profile.
+    pub verify: bool,
+}
+
+/// `(channel, ovp, ocp)` as returned by `MxSeries::protection_settings`.
+pub type ProtectionSetting = (u8, Option<f32>, Option<f32>);
+
 /// Actions for multi-channel on/off operations.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum MultiActionType {
     Quick,
     Never,
     Delay,
 }
 
+impl MultiActionType {
+    fn as_str(&self) -> &'static str {
+        match self {
+            MultiActionType::Quick => "QUICK",
+            MultiActionType::Never => "NEVER",
+            MultiActionType::Delay => "DELAY",
+        }
+    }
+}
+
+/// Parses the token used by the instrument, e.g. `"QUICK"`, ignoring case.
+impl FromStr for MultiActionType {
+    type Err = MxError;
+
+    fn from_str(s: &str) -> Result<Self, MxError> {
+        match s.trim().to_uppercase().as_str() {
+            "QUICK" => Ok(MultiActionType::Quick),
+            "NEVER" => Ok(MultiActionType::Never),
+            "DELAY" => Ok(MultiActionType::Delay),
+            _ => Err(MxError::Parse(format!("Unknown multi-on/off action: '{}'", s))),
+        }
+    }
+}
+
 /// Configuration for a multi-channel operation on a specific channel.
 #[derive(Debug, Clone, Copy)]
 pub enum MultiOperationConfig {
@@ -62,39 +406,639 @@ impl MeterAveraging {
     }
 }
 
+/// Parses the token used by the instrument, e.g. `"MED"`, ignoring case.
+impl FromStr for MeterAveraging {
+    type Err = MxError;
+
+    fn from_str(s: &str) -> Result<Self, MxError> {
+        match s.trim().to_uppercase().as_str() {
+            "ON" => Ok(MeterAveraging::On),
+            "OFF" => Ok(MeterAveraging::Off),
+            "LOW" => Ok(MeterAveraging::Low),
+            "MED" => Ok(MeterAveraging::Med),
+            "HIGH" => Ok(MeterAveraging::High),
+            _ => Err(MxError::Parse(format!("Unknown meter averaging setting: '{}'", s))),
+        }
+    }
+}
+
+/// How `MxSeries` treats the device-dependent error (verify timeout) bit of the Event Status
+/// Register, for use with `MxSeries::set_verify_timeout_policy`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum VerifyPolicy {
+    /// Fail the command with `MxError::VerifyTimeoutError`.
+    #[default]
+    Error,
+    /// Log a warning (with the `tracing` feature) and treat the command as successful.
+    Warn,
+}
+
+/// When `MxSeries` resends a command that failed, for use with `MxSeries::set_retry_policy`.
+///
+/// Only I/O errors, such as a timed out reply, are retried unless `retry_command_errors` is
+/// enabled. Before a command is resent after an I/O error, any unread input is discarded, so
+/// that a late reply to the failed attempt is not taken for the reply to the next one. The
+/// default policy never resends a command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    retries: u32,
+    retry_command_errors: bool,
+    status_query_retries: u32,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy::new(0)
+    }
+}
+
+impl RetryPolicy {
+    /// A policy resending a failed command up to `retries` times.
+    pub fn new(retries: u32) -> Self {
+        RetryPolicy { retries, retry_command_errors: false, status_query_retries: 0 }
+    }
+
+    /// How many more read timeouts to wait for the reply to the `*ESR?` and `EER?` queries of
+    /// the status check after a command, 0 by default.
+    ///
+    /// A busy instrument can be slow to answer them, which would otherwise hide the outcome
+    /// of the command behind an I/O error. The queries are not resent; the late reply is
+    /// read instead, so no extra reply is left for the next query.
+    pub fn status_query_retries(mut self, retries: u32) -> Self {
+        self.status_query_retries = retries;
+        self
+    }
+
+    /// Also retries commands the instrument reported as `MxError::CommandError`.
+    ///
+    /// Meant for serial adapters that occasionally corrupt a byte, which the instrument
+    /// reports as a syntax error although the command succeeds when resent. Off by default,
+    /// as a genuinely malformed command is then sent `retries` more times before the error
+    /// is returned.
+    pub fn retry_command_errors(mut self, enable: bool) -> Self {
+        self.retry_command_errors = enable;
+        self
+    }
+
+    fn _should_retry(&self, error: &MxError) -> bool {
+        match error {
+            MxError::Io(_) => true,
+            MxError::CommandError(_) => self.retry_command_errors,
+            _ => false,
+        }
+    }
+}
+
+
+/// Callback invoked with each command sent, its outcome and how long it took; see
+/// `MxSeries::set_command_hook`.
+pub type CommandHook = Box<dyn FnMut(&str, &Result<String, MxError>, Duration) + Send>;
+
+/// A command sent to the instrument, as kept by `MxSeries::command_log`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommandLogEntry {
+    /// When the command completed.
+    pub at: Instant,
+    pub command: String,
+    /// The reply (empty for writes), or the error message.
+    pub outcome: Result<String, String>,
+}
 
 /// Main struct for interacting with an MX Series power supply.
+///
+/// Methods taking a bare `u8` channel are kept for compatibility; prefer
+/// `MxSeries::channel` with a validated `Channel`.
 pub struct MxSeries {
     connection: Box<dyn Connection>,
+    /// `None` for connections supplied by the caller, which cannot be re-opened.
+    endpoint: Option<Endpoint>,
+    timeout: Option<Duration>,
+    write_char_delay: Option<Duration>,
+    read_terminator: Option<u8>,
+    detect_read_terminator: bool,
+    keepalive: Option<Duration>,
+    identity: Option<DeviceIdentity>,
+    esr_pending: u8,
+    dry_run: bool,
+    recorded_commands: Vec<String>,
+    trip_history: BTreeMap<u8, TripHistory>,
+    /// Last `VRANGE` index read or set on each channel, for `quantize_voltage`.
+    voltage_ranges: BTreeMap<u8, i32>,
+    compound_queries: bool,
+    esr_check: bool,
+    verify_timeout_policy: VerifyPolicy,
+    retry_policy: RetryPolicy,
+    cancel_flag: Option<Arc<AtomicBool>>,
+    reset_timeout: Duration,
+    verify_epsilon: f32,
+    verify_default: bool,
+    reset_trip_on_wait: bool,
+    echo_mode: bool,
+    /// Commands whose echo has not been read yet, in echo mode.
+    pending_echoes: VecDeque<String>,
+    /// In a mutex only so that `MxSeries` stays `Sync`.
+    command_hook: Option<Mutex<CommandHook>>,
+    command_log: VecDeque<CommandLogEntry>,
+    command_log_capacity: usize,
+}
+
+/// Where a connection was opened, so that it can be re-opened.
+#[derive(Debug, Clone)]
+enum Endpoint {
+    #[cfg(feature = "socket")]
+    Socket(String),
+    #[cfg(feature = "serial")]
+    Serial { port_name: String, baud_rate: u32 },
+}
+
+impl fmt::Display for Endpoint {
+    // Without a transport feature `Endpoint` has no variants and `f` is unused.
+    #[allow(unused_variables)]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            #[cfg(feature = "socket")]
+            Endpoint::Socket(ref address) => write!(f, "socket {}", address),
+            #[cfg(feature = "serial")]
+            Endpoint::Serial { ref port_name, baud_rate } => write!(f, "serial {} @ {} baud", port_name, baud_rate),
+        }
+    }
+}
+
+impl Endpoint {
+    fn open(&self) -> Result<Box<dyn Connection>, MxError> {
+        match *self {
+            #[cfg(feature = "socket")]
+            Endpoint::Socket(ref address) => Ok(Box::new(connection::SocketConnection::new(address)?)),
+            #[cfg(feature = "serial")]
+            Endpoint::Serial { ref port_name, baud_rate } => Ok(Box::new(connection::SerialConnection::new(port_name, baud_rate)?)),
+        }
+    }
 }
 
 impl MxSeries {
+    fn _open(endpoint: Endpoint) -> Result<Self, MxError> {
+        let connection = endpoint.open()?;
+        let mut psu = Self::from_connection(connection);
+        psu.endpoint = Some(endpoint);
+        Ok(psu)
+    }
+
+    /// Creates a new `MxSeries` instance over an already open connection, such as a
+    /// `connection::MockConnection` or a custom transport.
+    ///
+    /// `reconnect` is not available for such instances.
+    pub fn from_connection(connection: Box<dyn Connection>) -> Self {
+        MxSeries {
+            connection,
+            endpoint: None,
+            timeout: None,
+            write_char_delay: None,
+            read_terminator: None,
+            detect_read_terminator: false,
+            keepalive: None,
+            identity: None,
+            esr_pending: 0,
+            dry_run: false,
+            recorded_commands: Vec::new(),
+            trip_history: BTreeMap::new(),
+            voltage_ranges: BTreeMap::new(),
+            compound_queries: true,
+            esr_check: true,
+            verify_timeout_policy: VerifyPolicy::Error,
+            retry_policy: RetryPolicy::default(),
+            cancel_flag: None,
+            reset_timeout: DEFAULT_RESET_TIMEOUT,
+            verify_epsilon: VERIFY_TOLERANCE,
+            verify_default: false,
+            reset_trip_on_wait: false,
+            echo_mode: false,
+            pending_echoes: VecDeque::new(),
+            command_hook: None,
+            command_log: VecDeque::new(),
+            command_log_capacity: 0,
+        }
+    }
+
     /// Creates a new `MxSeries` instance with a socket connection.
     #[cfg(feature = "socket")]
     pub fn connect_socket(address: &str) -> Result<Self, MxError> {
-        let conn = connection::SocketConnection::new(address)?;
-        Ok(MxSeries {
-            connection: Box::new(conn),
-        })
+        Self::_open(Endpoint::Socket(address.to_string()))
     }
 
     /// Creates a new `MxSeries` instance with a serial connection.
     #[cfg(feature = "serial")]
     pub fn connect_serial(port_name: &str, baud_rate: u32) -> Result<Self, MxError> {
-        let conn = connection::SerialConnection::new(port_name, baud_rate)?;
-        Ok(MxSeries {
-            connection: Box::new(conn),
-        })
+        Self::_open(Endpoint::Serial { port_name: port_name.to_string(), baud_rate })
+    }
+
+    /// Creates a new `MxSeries` instance with a serial connection, trying each of the
+    /// `candidates` baud rates in turn until `*IDN?` gets a well-formed reply.
+    ///
+    /// A wrong rate usually gets no reply, so each unsuccessful candidate costs a read timeout.
+    /// Status registers are cleared once a rate is found, as the garbage received at the wrong
+    /// rates may have set error bits.
+    #[cfg(feature = "serial")]
+    pub fn connect_serial_autodetect(port_name: &str, candidates: &[u32]) -> Result<Self, MxError> {
+        let first = *candidates.first().ok_or_else(|| {
+            MxError::InvalidParameter("At least one candidate baud rate is required.".to_string())
+        })?;
+        let mut psu = Self::connect_serial(port_name, first)?;
+        for &baud_rate in candidates {
+            psu.reconfigure_serial(baud_rate)?;
+            if psu.refresh_identity().is_ok() {
+                psu.clear()?;
+                return Ok(psu);
+            }
+        }
+        Err(MxError::Timeout(format!(
+            "No identity reply on {} at any of the baud rates {:?}",
+            port_name, candidates
+        )))
+    }
+
+    /// Direct access to the underlying connection, for tuning it beyond what `MxSeries` exposes.
+    ///
+    /// Settings changed this way are not re-applied by `reconnect`.
+    pub fn connection_mut(&mut self) -> &mut dyn Connection {
+        self.connection.as_mut()
+    }
+
+    /// The transport of the underlying connection.
+    pub fn connection_kind(&self) -> ConnectionKind {
+        self.connection.connection_kind()
+    }
+
+    /// Direct access to the underlying connection as its concrete type, e.g.
+    /// `connection::SocketConnection`. Returns `None` if the connection is of another type
+    /// or does not support downcasting.
+    pub fn connection_downcast_mut<C: Connection + 'static>(&mut self) -> Option<&mut C> {
+        self.connection.as_any_mut()?.downcast_mut::<C>()
+    }
+
+    /// Writes exactly the given bytes to the instrument, without appending a line terminator.
+    ///
+    /// For the rare commands that misbehave with the terminator `write_command` appends.
+    /// No status check is performed. In echo mode the echo of each line written is discarded
+    /// before the next reply is read.
+    pub fn write_command_raw(&mut self, bytes: &[u8]) -> Result<(), MxError> {
+        if self.dry_run {
+            self.recorded_commands.push(String::from_utf8_lossy(bytes).into_owned());
+            return Ok(());
+        }
+        let started = Instant::now();
+        let text = String::from_utf8_lossy(bytes);
+        let outcome = self.connection.write_command_raw(bytes).map(|_| String::new());
+        if outcome.is_ok() && self.echo_mode {
+            // Each line is echoed back like a command sent with `_send`.
+            let lines = text.lines().map(str::trim).filter(|line| !line.is_empty());
+            self.pending_echoes.extend(lines.map(str::to_string));
+        }
+        self._record_command(&text, &outcome, started);
+        outcome.map(|_| ())
+    }
+
+    /// Enables or disables dry-run mode.
+    ///
+    /// In dry-run mode commands are appended to `recorded_commands` instead of being sent, and
+    /// no status checks are made. Anything that needs a reply from the instrument fails with
+    /// `MxError::UnsupportedFeature`.
+    pub fn set_dry_run(&mut self, dry_run: bool) {
+        self.dry_run = dry_run;
+    }
+
+    /// The commands recorded in dry-run mode, oldest first.
+    pub fn recorded_commands(&self) -> &[String] {
+        &self.recorded_commands
+    }
+
+    /// Returns the instrument to front-panel control with `LOCAL` and closes the connection.
+    ///
+    /// The connection is closed even if `LOCAL` fails; the first error is returned. A serial
+    /// port is released when this returns, so it can be reopened straight away.
+    pub fn close(self) -> Result<(), MxError> {
+        self._close(true)
+    }
+
+    /// Closes the connection without sending `LOCAL`, so the front panel stays locked out
+    /// until the instrument is next returned to local control.
+    pub fn close_keep_remote(self) -> Result<(), MxError> {
+        self._close(false)
+    }
+
+    fn _close(mut self, return_to_local: bool) -> Result<(), MxError> {
+        let local = if return_to_local { self._write("LOCAL") } else { Ok(()) };
+        let closed = if self.dry_run { Ok(()) } else { self.connection.close() };
+        local.and(closed)
+    }
+
+    /// Closes and re-opens the connection to the instrument.
+    ///
+    /// The communication timeout, write character delay, read terminator and keepalive are
+    /// re-applied and the cached identity is discarded.
+    pub fn reconnect(&mut self) -> Result<(), MxError> {
+        let endpoint = self.endpoint.as_ref().ok_or_else(|| {
+            MxError::UnsupportedFeature("Cannot reconnect a connection supplied by the caller".to_string())
+        })?;
+        self.identity = None;
+        self.voltage_ranges.clear();
+        self.pending_echoes.clear();
+        self.connection = endpoint.open()?;
+        if let Some(duration) = self.timeout {
+            self.connection.set_timeout(duration)?;
+        }
+        if let Some(delay) = self.write_char_delay {
+            self.connection.set_write_char_delay(delay)?;
+        }
+        if self.detect_read_terminator {
+            self.connection.detect_read_terminator()?;
+        } else if let Some(terminator) = self.read_terminator {
+            self.connection.set_read_terminator(terminator)?;
+        }
+        if let Some(interval) = self.keepalive {
+            self.connection.set_keepalive(interval)?;
+        }
+        Ok(())
+    }
+
+    /// Changes the baud rate of a serial connection in place, e.g. after connecting at the
+    /// wrong rate, keeping all other settings.
+    ///
+    /// Unread input, which is garbage at a wrong rate, is discarded along with the cached
+    /// identity, and `reconnect` uses the new rate. Fails with `MxError::UnsupportedFeature`
+    /// on other connections.
+    #[cfg(feature = "serial")]
+    pub fn reconfigure_serial(&mut self, baud_rate: u32) -> Result<(), MxError> {
+        let serial = self.connection_downcast_mut::<connection::SerialConnection>().ok_or_else(|| {
+            MxError::UnsupportedFeature("Baud rate can only be changed on serial connections".to_string())
+        })?;
+        let port = serial.port_mut()?;
+        port.set_baud_rate(baud_rate)?;
+        port.clear(serialport::ClearBuffer::Input)?;
+        if let Some(Endpoint::Serial { baud_rate: ref mut endpoint_rate, .. }) = self.endpoint {
+            *endpoint_rate = baud_rate;
+        }
+        self.identity = None;
+        self.pending_echoes.clear();
+        Ok(())
     }
 
     /// Sets the communication timeout for the connection.
     pub fn set_timeout(&mut self, duration: Duration) -> Result<(), MxError> {
-        self.connection.set_timeout(duration)
+        self.connection.set_timeout(duration)?;
+        self.timeout = Some(duration);
+        Ok(())
+    }
+
+    /// Sets the byte that ends a reply from the instrument, `b'\n'` by default.
+    ///
+    /// Use `b'\r'` for firmware that terminates replies with a carriage return only;
+    /// otherwise every read waits for the timeout.
+    pub fn set_read_terminator(&mut self, terminator: u8) -> Result<(), MxError> {
+        self.connection.set_read_terminator(terminator)?;
+        self.read_terminator = Some(terminator);
+        self.detect_read_terminator = false;
+        Ok(())
+    }
+
+    /// Detects whether replies end with LF, CRLF or CR from the first replies received,
+    /// instead of using a fixed read terminator.
+    ///
+    /// Until a reply shows the convention unambiguously, a reply ends at either CR or LF.
+    /// `set_read_terminator` switches detection off again.
+    pub fn detect_read_terminator(&mut self) -> Result<(), MxError> {
+        self.connection.detect_read_terminator()?;
+        self.detect_read_terminator = true;
+        Ok(())
+    }
+
+    /// Keeps an idle socket connection open by enabling TCP keepalive probes, sent after
+    /// `interval` without traffic and then every `interval` until answered.
+    ///
+    /// The probes are handled by the operating system, so no thread is needed and no commands
+    /// reach the instrument. They keep NAT and firewall state alive and detect a dead link, but
+    /// an instrument that closes connections it considers idle at the application level is not
+    /// fooled by them; in that case query something harmless such as `identify` periodically
+    /// instead. Fails with `MxError::UnsupportedFeature` on non-socket connections.
+    pub fn enable_keepalive(&mut self, interval: Duration) -> Result<(), MxError> {
+        self.connection.set_keepalive(interval)?;
+        self.keepalive = Some(interval);
+        Ok(())
+    }
+
+    /// Sets a delay inserted between each character written, for slow serial adapters.
+    ///
+    /// Zero (the default) writes each command in one go. Fails with
+    /// `MxError::UnsupportedFeature` on non-serial connections.
+    pub fn set_write_char_delay(&mut self, delay: Duration) -> Result<(), MxError> {
+        self.connection.set_write_char_delay(delay)?;
+        self.write_char_delay = Some(delay);
+        Ok(())
+    }
+
+    /// Sets how the device-dependent error bit of the Event Status Register is handled.
+    ///
+    /// Some firmware sets it spuriously during fast range changes although the command
+    /// succeeded; `VerifyPolicy::Warn` tolerates that while keeping the other status checks.
+    /// The default is `VerifyPolicy::Error`.
+    pub fn set_verify_timeout_policy(&mut self, policy: VerifyPolicy) {
+        self.verify_timeout_policy = policy;
+    }
+
+    /// Sets how far a read-back value may differ from the value written and still count as a
+    /// match in the verifying methods such as `verify_store` and `apply_profile`. The default
+    /// is 0.01; raise it to the display resolution of the instrument if read-backs are rounded.
+    pub fn set_verify_epsilon(&mut self, eps: f32) {
+        self.verify_epsilon = eps;
+    }
+
+    /// Makes `set_voltage`, `increment_voltage` and `decrement_voltage` always use the verify
+    /// form of their command, as if `verify` were `true`. Disabled by default.
+    pub fn set_verify_default(&mut self, enable: bool) {
+        self.verify_default = enable;
+    }
+
+    /// Whether the voltage setters verify by default, see `set_verify_default`.
+    pub fn verify_default(&self) -> bool {
+        self.verify_default
+    }
+
+    /// Makes `wait_trip_clear` send `TRIPRST` once before it starts polling. Disabled by
+    /// default, so that a latched trip is only cleared deliberately.
+    pub fn set_reset_trip_on_wait(&mut self, enable: bool) {
+        self.reset_trip_on_wait = enable;
+    }
+
+    /// Sets how long `reset` waits for the instrument to complete `*RST`, 10 seconds by default.
+    pub fn set_reset_timeout(&mut self, timeout: Duration) {
+        self.reset_timeout = timeout;
+    }
+
+    /// Sets when commands that fail are resent, e.g.
+    /// `RetryPolicy::new(2).retry_command_errors(true)`. By default commands are not resent.
+    ///
+    /// Retries resend the whole command, so a relative command such as `increment_voltage`
+    /// may be applied twice if only its status check failed.
+    pub fn set_retry_policy(&mut self, policy: RetryPolicy) {
+        self.retry_policy = policy;
+    }
+
+    /// Sets a flag that aborts long-running operations such as `wait_until_current_stable`
+    /// with `MxError::Cancelled`, e.g. when set from a UI thread. `None` removes it.
+    ///
+    /// The flag is checked between commands, never mid-command, and is not cleared when an
+    /// operation is cancelled.
+    pub fn set_cancel_flag(&mut self, flag: Option<Arc<AtomicBool>>) {
+        self.cancel_flag = flag;
+    }
+
+    fn _check_cancelled(&self) -> Result<(), MxError> {
+        match &self.cancel_flag {
+            Some(flag) if flag.load(Ordering::Relaxed) => Err(MxError::Cancelled),
+            _ => Ok(()),
+        }
+    }
+
+    /// Enables or disables echo mode, for adapters that echo each command back before the
+    /// instrument's reply.
+    ///
+    /// In echo mode a line matching each command sent is read and discarded before the next
+    /// reply is read. If the line read does not match, `MxError::Parse` is returned.
+    pub fn set_echo_mode(&mut self, enabled: bool) {
+        self.echo_mode = enabled;
+        self.pending_echoes.clear();
+    }
+
+    /// Sets a callback invoked after every command sent to the instrument, e.g. for logging
+    /// or kicking a watchdog. `None` removes it.
+    ///
+    /// The callback receives the command, its outcome and the time from sending the command to
+    /// its outcome, e.g. for latency metrics. The outcome is the reply for queries, an empty
+    /// string for writes, or the error. The replies to a batched query are joined with `;`.
+    /// Commands recorded in dry-run mode are not reported.
+    pub fn set_command_hook(&mut self, hook: Option<CommandHook>) {
+        self.command_hook = hook.map(Mutex::new);
+    }
+
+    /// Keeps the last `capacity` commands sent and their outcomes in memory, for
+    /// `command_log`. A capacity of 0, the default, disables the log.
+    ///
+    /// Unlike the `tracing` feature this needs no subscriber, e.g. to attach a transcript to a
+    /// bug report. Shrinking the capacity discards the oldest entries.
+    pub fn set_command_log_capacity(&mut self, capacity: usize) {
+        self.command_log_capacity = capacity;
+        while self.command_log.len() > capacity {
+            self.command_log.pop_front();
+        }
+    }
+
+    /// The commands kept by the command log, oldest first; see `set_command_log_capacity`.
+    pub fn command_log(&self) -> Vec<CommandLogEntry> {
+        self.command_log.iter().cloned().collect()
+    }
+
+    fn _record_command(&mut self, command: &str, outcome: &Result<String, MxError>, started: Instant) {
+        if self.command_log_capacity > 0 {
+            if self.command_log.len() == self.command_log_capacity {
+                self.command_log.pop_front();
+            }
+            self.command_log.push_back(CommandLogEntry {
+                at: Instant::now(),
+                command: command.to_string(),
+                outcome: match outcome {
+                    Ok(reply) => Ok(reply.clone()),
+                    Err(e) => Err(e.to_string()),
+                },
+            });
+        }
+        if let Some(hook) = &mut self.command_hook {
+            let hook = hook.get_mut().unwrap_or_else(PoisonError::into_inner);
+            hook(command, outcome, started.elapsed());
+        }
+    }
+
+    fn _write(&mut self, command: &str) -> Result<(), MxError> {
+        if self.dry_run {
+            self.recorded_commands.push(command.to_string());
+            return Ok(());
+        }
+        let started = Instant::now();
+        let outcome = self._send(command).map(|_| String::new());
+        self._record_command(command, &outcome, started);
+        outcome.map(|_| ())
+    }
+
+    fn _send(&mut self, command: &str) -> Result<(), MxError> {
+        self.connection.write_command(command)?;
+        if self.echo_mode {
+            self.pending_echoes.push_back(command.to_string());
+        }
+        Ok(())
+    }
+
+    fn _discard_echoes(&mut self) -> Result<(), MxError> {
+        while let Some(echo) = self.pending_echoes.pop_front() {
+            let line = self.connection.read_response()?;
+            if line != echo {
+                self.pending_echoes.clear();
+                return Err(MxError::Parse(format!("Expected echo of '{}', got '{}'", echo, line)));
+            }
+        }
+        Ok(())
+    }
+
+    fn _dry_run_error(command: &str) -> MxError {
+        MxError::UnsupportedFeature(format!("Query '{}' cannot be answered in dry-run mode", command))
+    }
+
+    fn _query(&mut self, command: &str) -> Result<String, MxError> {
+        if self.dry_run {
+            return Err(Self::_dry_run_error(command));
+        }
+        let started = Instant::now();
+        let outcome = self._send(command)
+            .and_then(|_| self._discard_echoes())
+            .and_then(|_| self.connection.read_response());
+        self._record_command(command, &outcome, started);
+        outcome
+    }
+
+    /// Reads another reply to `command` after an earlier read timed out, without resending it.
+    fn _read_late_reply(&mut self, command: &str) -> Result<String, MxError> {
+        let started = Instant::now();
+        let outcome = self.connection.read_response();
+        self._record_command(command, &outcome, started);
+        outcome
+    }
+
+    fn _execution_error(code: i32, command_sent: &str) -> MxError {
+        match EXECUTION_ERROR_CODES.get(&code) {
+            Some((err_type, err_msg)) => MxError::ExecutionError {
+                code,
+                error_type: err_type.to_string(),
+                description: err_msg.to_string(),
+            },
+            None => MxError::UndefinedDeviceErrorCode(code, command_sent.to_string()),
+        }
+    }
+
+    fn _status_query(&mut self, command: &str) -> Result<String, MxError> {
+        let mut reply = self._query(command);
+        // Wait for a late reply rather than resending the query, which would leave a second
+        // reply buffered for the next query to read.
+        for _ in 0..self.retry_policy.status_query_retries {
+            match reply {
+                Err(MxError::Io(_)) => {}
+                Ok(ref r) if r.is_empty() => {}
+                _ => break,
+            }
+            reply = self._read_late_reply(command);
+        }
+        reply
     }
 
     fn _check_event_status_register(&mut self, command_sent: &str) -> Result<(), MxError> {
         // Query the raw ESR value. *ESR? also clears it.
-        let esr_reply = match self.connection.query("*ESR?") {
+        let esr_reply = match self._status_query("*ESR?") {
             Ok(reply) => reply,
             Err(e) => return Err(MxError::Io(std::io::Error::new(
                 std::io::ErrorKind::Other,
@@ -102,8 +1046,9 @@ impl MxSeries {
             ))),
         };
 
+        // Merge in any bits already consumed by peek_esr.
         let status_val = match esr_reply.trim().parse::<u8>() {
-            Ok(val) => val,
+            Ok(val) => val | std::mem::take(&mut self.esr_pending),
             Err(_) => return Err(MxError::Parse(format!(
                 "Could not parse ESR value: '{}'. Original command: {}",
                 esr_reply, command_sent
@@ -121,24 +1066,29 @@ impl MxSeries {
             )));
         }
         if status_val & 0b00010000 != 0 { // Bit 4 - Execution Error
-            let eer_str = self.connection.query("EER?")?.trim().to_string();
-            let error_code = eer_str.parse::<i32>()
-                .map_err(|_| MxError::Parse(format!("Failed to parse EER value: {}", eer_str)))?;
-            
-            if let Some((err_type, err_msg)) = EXECUTION_ERROR_CODES.get(&error_code) {
-                return Err(MxError::ExecutionError {
-                    code: error_code,
-                    error_type: err_type.to_string(),
-                    description: err_msg.to_string(),
-                });
-            } else {
-                return Err(MxError::UndefinedDeviceErrorCode(error_code, command_sent.to_string()));
+            // A garbled EER? reply has been seen on a firmware glitch; ask once more before
+            // giving up.
+            let mut eer_str = self._status_query("EER?")?.trim().to_string();
+            if eer_str.parse::<i32>().is_err() {
+                eer_str = self._status_query("EER?")?.trim().to_string();
             }
+            let error_code = eer_str.parse::<i32>().map_err(|_| MxError::Parse(format!(
+                "Could not parse EER value: '{}'. Original command: {}",
+                eer_str, command_sent
+            )))?;
+
+            return Err(Self::_execution_error(error_code, command_sent));
         }
         if status_val & 0b00001000 != 0 { // Bit 3 - Device Dependent Error (Verify Timeout on MX)
-            return Err(MxError::VerifyTimeoutError(format!(
-                "Verify timeout or device dependent error. Command: '{}'", command_sent
-            )));
+            match self.verify_timeout_policy {
+                VerifyPolicy::Error => return Err(MxError::VerifyTimeoutError(format!(
+                    "Verify timeout or device dependent error. Command: '{}'", command_sent
+                ))),
+                VerifyPolicy::Warn => {
+                    #[cfg(feature = "tracing")]
+                    tracing::warn!(command = command_sent, "Ignoring verify timeout or device dependent error");
+                }
+            }
         }
         if status_val & 0b00000100 != 0 { // Bit 2 - Query Error
             return Err(MxError::QueryError(format!(
@@ -148,16 +1098,49 @@ impl MxSeries {
         Ok(())
     }
 
+    fn _with_retries<T>(&mut self, mut operation: impl FnMut(&mut Self) -> Result<T, MxError>) -> Result<T, MxError> {
+        let mut attempt = 0;
+        loop {
+            match operation(self) {
+                Err(e) if attempt < self.retry_policy.retries && self.retry_policy._should_retry(&e) => {
+                    attempt += 1;
+                    if matches!(e, MxError::Io(_)) {
+                        // A reply to the failed attempt may still arrive; it must not be read
+                        // as the reply to the next one.
+                        thread::sleep(COMMAND_DELAY);
+                        self.connection.clear_input()?;
+                    }
+                }
+                result => return result,
+            }
+        }
+    }
+
     fn _write_and_check(&mut self, command: &str) -> Result<(), MxError> {
-        self.connection.write_command(command)?;
+        self._with_retries(|psu| psu._write_and_check_once(command))
+    }
+
+    fn _write_and_check_once(&mut self, command: &str) -> Result<(), MxError> {
+        self._write(command)?;
+        if self.dry_run || !self.esr_check {
+            return Ok(());
+        }
         // A small delay can be crucial for the instrument to process the command
         // before its status registers are updated and checked.
-        thread::sleep(Duration::from_millis(50)); // Adjust as needed
+        thread::sleep(COMMAND_DELAY);
         self._check_event_status_register(command)
     }
 
     fn _query_and_check(&mut self, command: &str) -> Result<String, MxError> {
-        match self.connection.query(command) {
+        self._with_retries(|psu| psu._query_and_check_once(command))
+    }
+
+    fn _query_and_check_once(&mut self, command: &str) -> Result<String, MxError> {
+        if self.dry_run {
+            // Nothing was sent, so there is no status to check.
+            return Err(Self::_dry_run_error(command));
+        }
+        match self._query(command) {
             Ok(response) => {
                 // Even on successful query, check ESR for any latent errors from this command.
                 // This behavior might differ from the Python version's `except` block,
@@ -168,6 +1151,7 @@ impl MxSeries {
                 // self._check_event_status_register(command)?; // Potentially too strict
                 Ok(response.trim().to_string())
             }
+            Err(e) if !self.esr_check => Err(e),
             Err(e) => {
                 // If query itself fails (e.g. timeout, IO error), then check ESR.
                 // This is closer to the Python version's logic.
@@ -179,29 +1163,526 @@ impl MxSeries {
         }
     }
 
+    fn _channel_ranges(&mut self, channel: u8) -> Result<&'static [(f32, f32)], MxError> {
+        let identity = self.identify()?;
+        let channels = MODEL_RANGES.get(identity.model.as_str()).ok_or_else(|| {
+            MxError::UnsupportedFeature(format!("No range table for model '{}'", identity.model))
+        })?;
+        channel.checked_sub(1)
+            .and_then(|i| channels.get(i as usize))
+            .copied()
+            .ok_or_else(|| MxError::InvalidParameter(format!("Channel {} does not exist on the {}.", channel, identity.model)))
+    }
+
+    /// Take the interface lock, so that other interfaces cannot change settings, until the
+    /// returned guard is dropped.
+    ///
+    /// Fails with the `AccessDenied` execution error (code 200) if another interface holds
+    /// the lock. Dropping the guard releases the lock with `IFUNLOCK`; use
+    /// `InterfaceLock::release` to see whether that succeeded.
+    pub fn acquire_lock(&mut self) -> Result<InterfaceLock<'_>, MxError> {
+        // Reply format: "1" if granted, "-1" if another interface holds the lock
+        let reply: i32 = self.query_value("IFLOCK", parse::whole)?;
+        if reply != 1 {
+            return Err(Self::_execution_error(200, "IFLOCK"));
+        }
+        Ok(InterfaceLock { psu: self, released: false })
+    }
+
+    /// Apply a complete configuration, verify it and roll back to the previous settings if
+    /// any of them does not read back within `tolerance`.
+    ///
+    /// The settings of the channels in `profile` and the tracking mode are captured first,
+    /// then the profile is applied like `apply_profile`. If applying fails or a setting does
+    /// not match, the captured settings are written back; mismatches are reported as
+    /// `MxError::ConfigurationRolledBack` listing every failed setting. An error while rolling
+    /// back is returned instead, as the instrument is then in an unknown state.
+    pub fn apply_verified(&mut self, profile: &InstrumentProfile, tolerance: f32) -> Result<(), MxError> {
+        let previous_mode = match profile.tracking_mode {
+            Some(_) => Some(self.get_voltage_tracking_mode()?),
+            None => None,
+        };
+        let previous = profile.channels.keys()
+            .map(|&channel| self._read_channel_config(channel).map(|config| (channel, config)))
+            .collect::<Result<Vec<_>, MxError>>()?;
+
+        let outcome = self.apply_profile(&InstrumentProfile { verify: false, ..profile.clone() })
+            .and_then(|_| {
+                let mut mismatches = Vec::new();
+                if let Some(expected) = profile.tracking_mode {
+                    let actual = self.get_voltage_tracking_mode()?;
+                    if actual != expected {
+                        mismatches.push(format!("tracking mode: expected {}, got {}", expected, actual));
+                    }
+                }
+                for (&channel, expected) in &profile.channels {
+                    let actual = self._read_channel_config(channel)?;
+                    mismatches.extend(expected.mismatches(&actual, tolerance).into_iter()
+                        .map(|mismatch| format!("CH{} {}", channel, mismatch)));
+                }
+                Ok(mismatches)
+            });
+        if let Ok(mismatches) = &outcome {
+            if mismatches.is_empty() {
+                return Ok(());
+            }
+        }
+
+        if let Some(mode) = previous_mode {
+            self.set_voltage_tracking_mode(mode)?;
+        }
+        for (channel, config) in &previous {
+            self._write_channel_config(*channel, config)?;
+        }
+        Err(match outcome {
+            Ok(mismatches) => MxError::ConfigurationRolledBack { mismatches },
+            Err(e) => e,
+        })
+    }
+
+    /// Apply a complete configuration in as few commands as possible.
+    ///
+    /// The tracking mode is set first, as changing it affects the setpoints, followed by one
+    /// batched command per channel. The Event Status Register is checked once at the end
+    /// rather than after each command, so an error cannot be attributed to a single setting.
+    /// With `profile.verify` the settings are then read back and `MxError::ConfirmationMismatch`
+    /// is returned for the first channel that differs. Output states are not changed.
+    pub fn apply_profile(&mut self, profile: &InstrumentProfile) -> Result<(), MxError> {
+        let mut commands = Vec::new();
+        if let Some(mode) = profile.tracking_mode {
+            commands.push(format!("CONFIG {}", mode));
+        }
+        for (&channel, config) in &profile.channels {
+            commands.push(format!(
+                "V{channel} {voltage:.3};I{channel} {current:.3};{protection}",
+                channel = channel,
+                voltage = config.voltage,
+                current = config.current_limit,
+                protection = Self::_protection_clauses(
+                    channel,
+                    config.over_voltage_protection,
+                    config.over_current_protection,
+                ),
+            ));
+        }
+        for command in &commands {
+            self._write(command)?;
+        }
+        if self.dry_run {
+            return Ok(());
+        }
+        if self.esr_check {
+            thread::sleep(COMMAND_DELAY);
+            self._check_event_status_register(&commands.join(";"))?;
+        }
+        if profile.verify {
+            if let Some(mode) = profile.tracking_mode {
+                let actual = self.get_voltage_tracking_mode()?;
+                if actual != mode {
+                    return Err(MxError::ConfirmationMismatch {
+                        setting: "CONFIG".to_string(),
+                        expected: mode.to_string(),
+                        actual: actual.to_string(),
+                    });
+                }
+            }
+            for (&channel, expected) in &profile.channels {
+                let actual = self._read_channel_config(channel)?;
+                if !actual.matches(expected, self.verify_epsilon) {
+                    return Err(MxError::ConfirmationMismatch {
+                        setting: format!("channel {} configuration", channel),
+                        expected: format!("{:?}", expected),
+                        actual: format!("{:?}", actual),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Typed access to a single output channel.
+    pub fn channel(&mut self, channel: Channel) -> ChannelHandle<'_> {
+        ChannelHandle::new(self, channel)
+    }
+
+    /// Get the number of output channels of the instrument.
+    pub fn channel_count(&mut self) -> Result<u8, MxError> {
+        let identity = self.identify()?;
+        MODEL_RANGES.get(identity.model.as_str())
+            .map(|channels| channels.len() as u8)
+            .ok_or_else(|| MxError::UnsupportedFeature(format!("Unknown channel count for model '{}'", identity.model)))
+    }
+
+    /// Get the measured voltage, current, output state and regulation mode of the output channel.
+    pub fn channel_state(&mut self, channel: u8) -> Result<ChannelState, MxError> {
+        let measurement = self.measure(channel)?;
+        Ok(ChannelState {
+            channel,
+            voltage: measurement.voltage,
+            current: measurement.current,
+            output_on: self.is_output_on(channel)?,
+            mode: self.operating_mode(channel)?,
+        })
+    }
+
+    fn _parse_status_lines(lines: &[String]) -> Result<(f32, f32, f32, f32, u8), MxError> {
+        Ok((
+            parse::value_token(&lines[0])?,
+            parse::with_suffix('V')(&lines[1])?,
+            parse::value_token(&lines[2])?,
+            parse::with_suffix('A')(&lines[3])?,
+            parse::whole(&lines[4])?,
+        ))
+    }
+
+    /// Get the setpoints, measurements, regulation mode and trip status of the output channel.
+    ///
+    /// Everything is read with a single batched query. If the instrument rejects batched
+    /// queries, individual queries are used instead, for this and all later calls.
+    pub fn channel_status_line(&mut self, channel: u8) -> Result<ChannelStatusLine, MxError> {
+        let (voltage_setpoint, voltage, current_limit, current, lsr) = if self.compound_queries {
+            let command = format!("V{n}?;V{n}O?;I{n}?;I{n}O?;LSR{n}?", n = channel);
+            match self.query_lines(&command, 5) {
+                Ok(lines) => Self::_parse_status_lines(&lines).map_err(|e| Self::_with_context(e, &command))?,
+                Err(MxError::CommandError(_)) => {
+                    self.compound_queries = false;
+                    return self.channel_status_line(channel);
+                }
+                Err(e) => return Err(e),
+            }
+        } else {
+            (
+                self.get_voltage_setpoint(channel)?,
+                self.get_voltage(channel)?,
+                self.get_current_limit(channel)?,
+                self.get_current(channel)?,
+                self.limit_status_register(channel)?,
+            )
+        };
+        Ok(ChannelStatusLine {
+            channel,
+            voltage_setpoint,
+            voltage,
+            current_limit,
+            current,
+            mode: Self::_decode_operating_mode(lsr),
+            trip: self._observe_trip_status(channel, lsr),
+        })
+    }
+
+    /// Get the trip status of the output channel from its limit status register.
+    ///
+    /// Newly observed over-voltage and over-current trips are counted in `trip_counts`.
+    pub fn channel_trip_status(&mut self, channel: u8) -> Result<TripStatus, MxError> {
+        let lsr = self.limit_status_register(channel)?;
+        Ok(self._observe_trip_status(channel, lsr))
+    }
+
+    fn _observe_trip_status(&mut self, channel: u8, lsr: u8) -> TripStatus {
+        let status = TripStatus {
+            over_voltage: lsr & 0b00000100 != 0, // Bit 2 - Over-voltage trip
+            over_current: lsr & 0b00001000 != 0, // Bit 3 - Over-current trip
+            hardware: lsr & 0b01000000 != 0,     // Bit 6 - Hardware (sense/thermal) trip
+        };
+        let history = self.trip_history.entry(channel).or_default();
+        if status.over_voltage && !history.last.over_voltage {
+            history.ovp_trips += 1;
+        }
+        if status.over_current && !history.last.over_current {
+            history.ocp_trips += 1;
+        }
+        let new_trip = (status.over_voltage && !history.last.over_voltage)
+            || (status.over_current && !history.last.over_current)
+            || (status.hardware && !history.last.hardware);
+        if new_trip {
+            history.last_trip = Some(Instant::now());
+        }
+        history.last = status;
+        status
+    }
+
+    fn _decode_operating_mode(lsr: u8) -> OperatingMode {
+        if lsr & 0b00000010 != 0 { // Bit 1 - Current limit (CC)
+            OperatingMode::ConstantCurrent
+        } else if lsr & 0b00000001 != 0 { // Bit 0 - Voltage limit (CV)
+            OperatingMode::ConstantVoltage
+        } else {
+            OperatingMode::Unregulated
+        }
+    }
+
+    fn _with_context(err: MxError, command: &str) -> MxError {
+        match err {
+            MxError::Parse(msg) => MxError::Parse(format!("{} (reply to {})", msg, command)),
+            other => other,
+        }
+    }
+
+    fn _read_channel_config(&mut self, channel: u8) -> Result<ChannelConfig, MxError> {
+        let (over_voltage_protection, over_current_protection) = self.get_protection_limits(channel)?;
+        Ok(ChannelConfig {
+            voltage: self.get_voltage_setpoint(channel)?,
+            current_limit: self.get_current_limit(channel)?,
+            over_voltage_protection,
+            over_current_protection,
+        })
+    }
+
+    fn _write_channel_config(&mut self, channel: u8, config: &ChannelConfig) -> Result<(), MxError> {
+        self.set_voltage(channel, config.voltage, false)?;
+        self.set_current_limit(channel, config.current_limit)?;
+        let ovp = config.over_voltage_protection;
+        self.set_over_voltage_protection(channel, ovp.is_some(), ovp)?;
+        let ocp = config.over_current_protection;
+        self.set_over_current_protection(channel, ocp.is_some(), ocp)
+    }
+
+    /// Sends `query(n)` for every channel as one batched query and parses the replies of each
+    /// channel with `parse`. `query` must send one query per `;`-separated part.
+    ///
+    /// Returns `None` if the instrument rejects batched queries; they are then not attempted
+    /// again and the caller should fall back to querying each channel.
+    fn _batched_channel_query<T>(
+        &mut self,
+        query: impl Fn(u8) -> String,
+        parse: impl Fn(u8, &[String]) -> Result<T, MxError>,
+    ) -> Result<Option<Vec<T>>, MxError> {
+        if !self.compound_queries {
+            return Ok(None);
+        }
+        let channels = 1..=self.channel_count()?;
+        let per_channel = query(1).split(';').count();
+        let command = channels.clone().map(query).collect::<Vec<_>>().join(";");
+        match self.query_lines(&command, per_channel * channels.len()) {
+            Ok(lines) => channels.zip(lines.chunks(per_channel))
+                .map(|(channel, lines)| parse(channel, lines))
+                .collect::<Result<_, MxError>>()
+                .map(Some)
+                .map_err(|e| Self::_with_context(e, &command)),
+            Err(MxError::CommandError(_)) => {
+                self.compound_queries = false;
+                Ok(None)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Rejects a negative, NaN or infinite setpoint before it is formatted into a command.
+    fn _check_setpoint(setting: &str, value: f32) -> Result<(), MxError> {
+        if value >= 0.0 && value.is_finite() {
+            Ok(())
+        } else {
+            Err(MxError::InvalidParameter(format!("{} must be a non-negative number, got {}.", setting, value)))
+        }
+    }
+
+    fn _check_readback(&self, setting: &str, expected: f32, actual: Option<f32>) -> Result<(), MxError> {
+        match actual {
+            Some(actual) if approx_eq(actual, expected, self.verify_epsilon) => Ok(()),
+            _ => Err(MxError::ConfirmationMismatch {
+                setting: setting.to_string(),
+                expected: format!("{:.3}", expected),
+                actual: actual.map_or("OFF".to_string(), |actual| format!("{:.3}", actual)),
+            }),
+        }
+    }
+
+    /// The batched `OVP`/`OCP` clauses that enable and set, or disable, both protections.
+    fn _protection_clauses(channel: u8, ovp: Option<f32>, ocp: Option<f32>) -> String {
+        let clause = |kind: &str, value: Option<f32>| match value {
+            Some(value) => format!("{kind}{channel} ON;{kind}{channel} {value:.3}", kind = kind, channel = channel, value = value),
+            None => format!("{}{} OFF", kind, channel),
+        };
+        format!("{};{}", clause("OVP", ovp), clause("OCP", ocp))
+    }
+
+    /// Shared implementation of the OVP/OCP setters. `kind` is the command header.
+    fn _set_protection(
+        &mut self,
+        kind: &str,
+        channel: u8,
+        enable: bool,
+        value: Option<f32>,
+        read_back: fn(&mut Self, u8) -> Result<Option<f32>, MxError>,
+    ) -> Result<(), MxError> {
+        if !enable {
+            return self._write_and_check(&format!("{}{} OFF", kind, channel));
+        }
+        let val = value.ok_or_else(|| MxError::InvalidParameter(format!("Must specify {} value if enabling.", kind)))?;
+        Self::_check_setpoint(&format!("{}{}", kind, channel), val)?;
+        self._write_and_check(&format!("{}{} ON", kind, channel))?;
+        self._write_and_check(&format!("{}{} {:.3}", kind, channel, val))
+            .map_err(|err| MxError::ProtectionValueRejected {
+                setting: format!("{}{}", kind, channel),
+                source: Box::new(err),
+            })?;
+        if !self.dry_run {
+            let actual = read_back(self, channel)?;
+            self._check_readback(&format!("{}{}", kind, channel), val, actual)?;
+        }
+        Ok(())
+    }
+
+    fn _meter_resolution(full_scale: f32) -> f32 {
+        10f32.powi(full_scale.log10().floor() as i32 + 1 - METER_DIGITS)
+    }
+
+    /// The range the output channel is set to.
+    fn _active_range(&mut self, channel: u8) -> Result<RangeInfo, MxError> {
+        let ranges = self._channel_ranges(channel)?;
+        let index = self.get_voltage_range(channel)?;
+        usize::try_from(index)
+            .ok()
+            .and_then(|index| index.checked_sub(1))
+            .and_then(|i| ranges.get(i))
+            .map(|&(max_voltage, max_current)| RangeInfo { index, max_voltage, max_current })
+            .ok_or_else(|| MxError::Parse(format!("Unexpected voltage range index {} for channel {}", index, channel)))
+    }
+
+    /// Rounds `value` to the setpoint resolution of the channel's last known range, or of the
+    /// lowest range that covers it if none is known, where `pick` selects the voltage or
+    /// current maximum of a range.
+    fn _quantize(&self, channel: u8, value: f32, pick: fn(&(f32, f32)) -> f32) -> f32 {
+        let ranges = self.identity.as_ref()
+            .and_then(|identity| MODEL_RANGES.get(identity.model.as_str()))
+            .and_then(|channels| channels.get(channel.checked_sub(1)? as usize));
+        let full_scale = ranges.and_then(|ranges| {
+            let active = self.voltage_ranges.get(&channel)
+                .and_then(|&index| usize::try_from(index).ok()?.checked_sub(1))
+                .and_then(|i| ranges.get(i));
+            match active {
+                Some(range) => Some(pick(range)),
+                None => ranges.iter().map(pick).filter(|&max| max >= value.abs()).reduce(f32::min),
+            }
+        });
+        let step = full_scale.map_or(SETPOINT_STEP, |max| Self::_meter_resolution(max).max(SETPOINT_STEP));
+        (value / step).round() * step
+    }
+
     /// Send the clear, `*CLS`, command. This clears status registers.
     pub fn clear(&mut self) -> Result<(), MxError> {
-        self.connection.write_command("*CLS")
+        self.esr_pending = 0;
+        self._write("*CLS")
         // Do not call _check_event_status_register here as *CLS clears it.
     }
 
-    /// Decrement the current limit by step size of the output channel.
-    pub fn decrement_current(&mut self, channel: u8) -> Result<(), MxError> {
-        self._write_and_check(&format!("DECI{}", channel))
+    /// Decrement the current limit by step size of the output channel.
+    pub fn decrement_current(&mut self, channel: u8) -> Result<(), MxError> {
+        self._write_and_check(&format!("DECI{}", channel))
+    }
+
+    /// Decrement the voltage by step size of the output channel.
+    pub fn decrement_voltage(&mut self, channel: u8, verify: bool) -> Result<(), MxError> {
+        let command = format!("DECV{}{}", channel, if verify || self.verify_default { "V" } else { "" });
+        self._write_and_check(&command)
+    }
+
+    /// Get the active voltage range of the output channel with its voltage and current limits,
+    /// looked up by model from the range table.
+    ///
+    /// Fails with `MxError::UnsupportedFeature` for models not in the table.
+    pub fn describe_range(&mut self, channel: u8) -> Result<RangeInfo, MxError> {
+        self._active_range(channel)
+    }
+
+    /// Like `diagnostics_dump`, but also drains the pending execution errors into the report.
+    ///
+    /// Reading the errors clears them, so use this once something has gone wrong rather than
+    /// for routine logging.
+    pub fn diagnostics(&mut self) -> Result<DiagnosticsReport, MxError> {
+        let mut report = self.diagnostics_dump()?;
+        report.execution_errors = self.drain_errors()?;
+        Ok(report)
+    }
+
+    /// Collect the identity, status registers, setpoints, protection settings and measurements
+    /// of the instrument into one report, e.g. for a bug report.
+    ///
+    /// The Event Status Register is read with `peek_esr`, so its bits are still reported by
+    /// the next status check.
+    pub fn diagnostics_dump(&mut self) -> Result<DiagnosticsReport, MxError> {
+        let identity = self.refresh_identity()?;
+        let event_status_register = self.peek_esr()?;
+        // Reply format: "0" (integer)
+        let status_byte = self.query_value("*STB?", parse::whole)?;
+        let limit_status: Vec<(u8, u8)> = (1..=self.channel_count()?)
+            .map(|channel| self.limit_status_register(channel).map(|lsr| (channel, lsr)))
+            .collect::<Result<_, MxError>>()?;
+        let trips = limit_status.iter()
+            .map(|&(channel, lsr)| (channel, self._observe_trip_status(channel, lsr)))
+            .collect();
+        Ok(DiagnosticsReport {
+            identity,
+            event_status_register,
+            status_byte,
+            limit_status,
+            trips,
+            execution_errors: Vec::new(),
+            setpoints: self.get_setpoints()?,
+            measurements: self.measure_all()?,
+        })
+    }
+
+    /// Read the Execution Error Register with `EER?` until it reports no error, and return
+    /// each error read as `(code, type, description)`.
+    ///
+    /// `EER?` clears the register, so this discards every pending execution error. Codes
+    /// missing from the error table are returned with the type `"Undefined"`.
+    pub fn drain_errors(&mut self) -> Result<Vec<(i32, String, String)>, MxError> {
+        // Bounds the loop should the instrument keep reporting an error.
+        const MAX_ERRORS: usize = 32;
+        let mut errors = Vec::new();
+        while errors.len() < MAX_ERRORS {
+            let code: i32 = self.query_value("EER?", parse::whole)?;
+            if code == 0 {
+                break;
+            }
+            let (error_type, description) = EXECUTION_ERROR_CODES.get(&code)
+                .copied()
+                .unwrap_or(("Undefined", "Error code not listed in the manual."));
+            errors.push((code, error_type.to_string(), description.to_string()));
+        }
+        Ok(errors)
     }
 
-    /// Decrement the voltage by step size of the output channel.
-    pub fn decrement_voltage(&mut self, channel: u8, verify: bool) -> Result<(), MxError> {
-        let command = format!("DECV{}{}", channel, if verify { "V" } else { "" });
+    /// Turn every output off and set every voltage to zero in a single batched command.
+    ///
+    /// `OPALL 0` comes first, so the outputs are off as soon as the instrument parses the
+    /// command, and the status is checked once at the end. The voltages are zeroed on each
+    /// channel of the cached identity, or on every channel an MX Series model can have if none
+    /// is cached, so no `*IDN?` round trip delays the command. An error is returned if any
+    /// voltage could not be zeroed, e.g. with tracking enabled or on a model with fewer
+    /// channels; the outputs are off even then. Unlike `close`, control is not returned to
+    /// the front panel.
+    pub fn emergency_off(&mut self) -> Result<(), MxError> {
+        let count = self.identity.as_ref()
+            .and_then(|identity| MODEL_RANGES.get(identity.model.as_str()))
+            .map_or(channel::MAX_CHANNEL, |channels| channels.len() as u8);
+        let mut command = "OPALL 0".to_string();
+        for channel in 1..=count {
+            command.push_str(&format!(";V{} 0", channel));
+        }
         self._write_and_check(&command)
     }
 
+    /// Disable the automatic status check after each command until the returned guard is dropped.
+    ///
+    /// Skipping the check saves the command delay and a `*ESR?` round trip per write, which
+    /// adds up in long command sequences. The cost is that errors are not reported by the
+    /// command that caused them: the status register keeps accumulating, so they surface at
+    /// the next checked command (or `event_status_register`) instead. The previous setting is
+    /// restored when the guard is dropped, including on early return with `?`.
+    pub fn esr_check_paused(&mut self) -> EsrCheckGuard<'_> {
+        let previous = std::mem::replace(&mut self.esr_check, false);
+        EsrCheckGuard { psu: self, previous }
+    }
+
     /// Read and clear the standard event status register.
     pub fn event_status_register(&mut self, as_integer: bool) -> Result<ESRValue, MxError> {
-        let val_str = self.connection.query("*ESR?")?; // *ESR? reads and clears
+        let val_str = self._query("*ESR?")?; // *ESR? reads and clears
         let value = val_str.trim().parse::<u8>().map_err(|e| {
             MxError::Parse(format!("Failed to parse ESR value '{}': {}", val_str, e))
-        })?;
+        })? | std::mem::take(&mut self.esr_pending);
         if as_integer {
             Ok(ESRValue::Integer(value))
         } else {
@@ -209,120 +1690,227 @@ impl MxSeries {
         }
     }
 
+    /// Get the firmware version reported by `*IDN?`.
+    pub fn firmware_version(&mut self) -> Result<String, MxError> {
+        Ok(self.identify()?.firmware)
+    }
+
+    /// Get the maximum voltage of each range of the output channel.
+    ///
+    /// The MX firmware cannot report its ranges, so they are looked up from the model reported
+    /// by `*IDN?`. Element `i` of the result is the range selected by `set_voltage_range(channel, i + 1)`.
+    pub fn get_available_ranges(&mut self, channel: u8) -> Result<Vec<f32>, MxError> {
+        let ranges = self._channel_ranges(channel)?;
+        Ok(ranges.iter().map(|(max_voltage, _)| *max_voltage).collect())
+    }
+
+    /// Get the maximum voltage and current the output channel can produce on any range.
+    ///
+    /// Looked up from the model reported by `*IDN?`. The two maximums are usually on
+    /// different ranges, so they cannot necessarily be drawn at the same time.
+    pub fn get_max_ratings(&mut self, channel: u8) -> Result<(f32, f32), MxError> {
+        let ranges = self._channel_ranges(channel)?;
+        let max_voltage = ranges.iter().map(|&(voltage, _)| voltage).fold(0.0, f32::max);
+        let max_current = ranges.iter().map(|&(_, current)| current).fold(0.0, f32::max);
+        Ok((max_voltage, max_current))
+    }
+
     /// Get the output current of the output channel.
     pub fn get_current(&mut self, channel: u8) -> Result<f32, MxError> {
-        let reply = self._query_and_check(&format!("I{}O?", channel))?;
+        self.get_current_f64(channel).map(|value| value as f32)
+    }
+
+    /// Get the output current of the output channel at full `f64` precision.
+    pub fn get_current_f64(&mut self, channel: u8) -> Result<f64, MxError> {
         // Reply format: "1.234A"
-        if let Some(val_str) = reply.strip_suffix('A') {
-            val_str.parse::<f32>().map_err(MxError::from)
-        } else {
-            Err(MxError::Parse(format!("Unexpected format for get_current (I{}O?): '{}'", channel, reply)))
-        }
+        self.query_value(&format!("I{}O?", channel), parse::with_suffix('A'))
     }
 
     /// Get the current limit of the output channel.
     pub fn get_current_limit(&mut self, channel: u8) -> Result<f32, MxError> {
-        let reply = self._query_and_check(&format!("I{}?", channel))?;
         // Reply format: "I1 0.500"
-        let parts: Vec<&str> = reply.split_whitespace().collect();
-        if parts.len() == 2 {
-            parts[1].parse::<f32>().map_err(MxError::from)
-        } else {
-            Err(MxError::Parse(format!("Unexpected format for get_current_limit (I{}?): '{}'", channel, reply)))
-        }
+        self.query_value(&format!("I{}?", channel), parse::value_token)
     }
 
     /// Get the current limit step size of the output channel.
     pub fn get_current_step_size(&mut self, channel: u8) -> Result<f32, MxError> {
-        let reply = self._query_and_check(&format!("DELTAI{}?", channel))?;
         // Reply format: "DELTAI1 0.010"
-        let parts: Vec<&str> = reply.split_whitespace().collect();
-        if parts.len() == 2 {
-            parts[1].parse::<f32>().map_err(MxError::from)
-        } else {
-            Err(MxError::Parse(format!("Unexpected format for get_current_step_size (DELTAI{}?): '{}'", channel, reply)))
+        self.query_value(&format!("DELTAI{}?", channel), parse::value_token)
+    }
+
+    /// Check whether each output is on, starting with channel 1.
+    ///
+    /// All channels are read with a single batched query, falling back to `is_output_on` per
+    /// channel like `measure_all`.
+    pub fn get_output_states(&mut self) -> Result<Vec<bool>, MxError> {
+        let batched = self._batched_channel_query(|n| format!("OP{}?", n), |_, lines| parse::flag(&lines[0]))?;
+        match batched {
+            Some(states) => Ok(states),
+            None => (1..=self.channel_count()?).map(|channel| self.is_output_on(channel)).collect(),
         }
     }
-    
+
     /// Get the over-current protection trip point of the output channel.
     pub fn get_over_current_protection(&mut self, channel: u8) -> Result<Option<f32>, MxError> {
-        let reply = self._query_and_check(&format!("OCP{}?", channel))?;
         // Reply format: "OCP1 1.500" or "OCP1 OFF"
-        if reply.to_uppercase().ends_with("OFF") {
-            Ok(None)
-        } else {
-            let parts: Vec<&str> = reply.split_whitespace().collect();
-            if parts.len() == 2 {
-                parts[1].parse::<f32>().map(Some).map_err(MxError::from)
-            } else {
-                Err(MxError::Parse(format!("Unexpected format for get_over_current_protection (OCP{}?): '{}'", channel, reply)))
-            }
-        }
+        self.query_value(&format!("OCP{}?", channel), parse::optional_value_token)
     }
 
     /// Get the over-voltage protection trip point of the output channel.
     pub fn get_over_voltage_protection(&mut self, channel: u8) -> Result<Option<f32>, MxError> {
-        let reply = self._query_and_check(&format!("OVP{}?", channel))?;
         // Reply format: "OVP1 30.50" or "OVP1 OFF"
-         if reply.to_uppercase().ends_with("OFF") {
-            Ok(None)
-        } else {
-            let parts: Vec<&str> = reply.split_whitespace().collect();
-            if parts.len() == 2 {
-                parts[1].parse::<f32>().map(Some).map_err(MxError::from)
-            } else {
-                Err(MxError::Parse(format!("Unexpected format for get_over_voltage_protection (OVP{}?): '{}'", channel, reply)))
+        self.query_value(&format!("OVP{}?", channel), parse::optional_value_token)
+    }
+
+    /// Get the voltage and current measurement resolution of the output channel on its
+    /// present range.
+    ///
+    /// Derived from the range maximums in the model table, assuming the meters' five
+    /// significant digits, e.g. 0.001V and 0.0001A on the MX100's 16V/6A range.
+    pub fn get_resolution(&mut self, channel: u8) -> Result<(f32, f32), MxError> {
+        let RangeInfo { max_voltage, max_current, .. } = self._active_range(channel)?;
+        Ok((Self::_meter_resolution(max_voltage), Self::_meter_resolution(max_current)))
+    }
+
+    /// Get the voltage and current measurement resolution of the output channel, the same
+    /// as `get_resolution`.
+    pub fn meter_resolution(&mut self, channel: u8) -> Result<(f32, f32), MxError> {
+        self.get_resolution(channel)
+    }
+
+    /// Get the commanded settings of every channel, without any measurements.
+    ///
+    /// All channels are read with a single batched query, falling back to the individual
+    /// getters per channel like `measure_all`.
+    pub fn get_setpoints(&mut self) -> Result<Vec<ChannelSetpoints>, MxError> {
+        let batched = self._batched_channel_query(
+            |n| format!("V{n}?;I{n}?;OVP{n}?;OCP{n}?;VRANGE{n}?", n = n),
+            |channel, lines| Ok(ChannelSetpoints {
+                channel,
+                voltage: parse::value_token(&lines[0])?,
+                current_limit: parse::value_token(&lines[1])?,
+                over_voltage_protection: parse::optional_value_token(&lines[2])?,
+                over_current_protection: parse::optional_value_token(&lines[3])?,
+                range: parse::whole(&lines[4])?,
+            }),
+        )?;
+        if let Some(setpoints) = batched {
+            for setpoint in &setpoints {
+                self.voltage_ranges.insert(setpoint.channel, setpoint.range);
             }
+            return Ok(setpoints);
         }
+        (1..=self.channel_count()?)
+            .map(|channel| Ok(ChannelSetpoints {
+                channel,
+                voltage: self.get_voltage_setpoint(channel)?,
+                current_limit: self.get_current_limit(channel)?,
+                over_voltage_protection: self.get_over_voltage_protection(channel)?,
+                over_current_protection: self.get_over_current_protection(channel)?,
+                range: self.get_voltage_range(channel)?,
+            }))
+            .collect()
     }
 
     /// Get the output voltage of the output channel.
     pub fn get_voltage(&mut self, channel: u8) -> Result<f32, MxError> {
-        let reply = self._query_and_check(&format!("V{}O?", channel))?;
+        self.get_voltage_f64(channel).map(|value| value as f32)
+    }
+
+    /// Get the output voltage of the output channel at full `f64` precision.
+    pub fn get_voltage_f64(&mut self, channel: u8) -> Result<f64, MxError> {
         // Reply format: "5.000V"
-        if let Some(val_str) = reply.strip_suffix('V') {
-            val_str.parse::<f32>().map_err(MxError::from)
-        } else {
-             Err(MxError::Parse(format!("Unexpected format for get_voltage (V{}O?): '{}'", channel, reply)))
-        }
+        self.query_value(&format!("V{}O?", channel), parse::with_suffix('V'))
     }
 
     /// Get the output voltage range index of the output channel.
     pub fn get_voltage_range(&mut self, channel: u8) -> Result<i32, MxError> {
-        let reply = self._query_and_check(&format!("VRANGE{}?", channel))?;
         // Reply format: "1" (integer)
-        reply.parse::<i32>().map_err(MxError::from)
+        let index = self.query_value(&format!("VRANGE{}?", channel), parse::whole)?;
+        self.voltage_ranges.insert(channel, index);
+        Ok(index)
+    }
+
+    /// Get the over-voltage protection trip point of the output channel as a percentage of its
+    /// voltage setpoint, or `None` if over-voltage protection is disabled.
+    pub fn get_ovp_percent(&mut self, channel: u8) -> Result<Option<f32>, MxError> {
+        let ovp = match self.get_over_voltage_protection(channel)? {
+            Some(ovp) => ovp,
+            None => return Ok(None),
+        };
+        let setpoint = self.get_voltage_setpoint(channel)?;
+        if setpoint <= 0.0 {
+            return Err(MxError::InvalidParameter(format!("Voltage setpoint of channel {} is zero.", channel)));
+        }
+        Ok(Some(ovp / setpoint * 100.0))
+    }
+
+    /// Get the over-voltage and over-current protection trip points of the output channel.
+    ///
+    /// Returns `(ovp, ocp)`, where `None` means that protection is disabled. Both are read
+    /// with a single batched query if the firmware supports compound queries, and with
+    /// `get_over_voltage_protection` and `get_over_current_protection` otherwise.
+    pub fn get_protection_limits(&mut self, channel: u8) -> Result<(Option<f32>, Option<f32>), MxError> {
+        if self.compound_queries {
+            let command = format!("OVP{channel}?;OCP{channel}?", channel = channel);
+            match self.query_lines(&command, 2) {
+                Ok(lines) => {
+                    let ovp = parse::optional_value_token(&lines[0]).map_err(|e| Self::_with_context(e, &command))?;
+                    let ocp = parse::optional_value_token(&lines[1]).map_err(|e| Self::_with_context(e, &command))?;
+                    return Ok((ovp, ocp));
+                }
+                Err(MxError::CommandError(_)) => self.compound_queries = false,
+                Err(e) => return Err(e),
+            }
+        }
+        Ok((self.get_over_voltage_protection(channel)?, self.get_over_current_protection(channel)?))
     }
 
     /// Get the set-point voltage of the output channel.
     pub fn get_voltage_setpoint(&mut self, channel: u8) -> Result<f32, MxError> {
-        let reply = self._query_and_check(&format!("V{}?", channel))?;
         // Reply format: "V1 5.000"
-        let parts: Vec<&str> = reply.split_whitespace().collect();
-        if parts.len() == 2 {
-            parts[1].parse::<f32>().map_err(MxError::from)
-        } else {
-            Err(MxError::Parse(format!("Unexpected format for get_voltage_setpoint (V{}?): '{}'", channel, reply)))
-        }
+        self.query_value(&format!("V{}?", channel), parse::value_token)
     }
 
     /// Get the voltage step size of the output channel.
     pub fn get_voltage_step_size(&mut self, channel: u8) -> Result<f32, MxError> {
-        let reply = self._query_and_check(&format!("DELTAV{}?", channel))?;
         // Reply format: "DELTAV1 0.010"
-        let parts: Vec<&str> = reply.split_whitespace().collect();
-        if parts.len() == 2 {
-            parts[1].parse::<f32>().map_err(MxError::from)
-        } else {
-            Err(MxError::Parse(format!("Unexpected format for get_voltage_step_size (DELTAV{}?): '{}'", channel, reply)))
-        }
+        self.query_value(&format!("DELTAV{}?", channel), parse::value_token)
     }
 
     /// Get the voltage tracking mode of the unit.
     pub fn get_voltage_tracking_mode(&mut self) -> Result<i32, MxError> {
-        let reply = self._query_and_check("CONFIG?")?;
         // Reply format: "0" (integer)
-        reply.parse::<i32>().map_err(MxError::from)
+        self.query_value("CONFIG?", parse::whole)
+    }
+
+    /// Query the identity, `*IDN?`, of the instrument.
+    ///
+    /// The identity is cached after the first successful query; use `refresh_identity` to re-query it.
+    pub fn identify(&mut self) -> Result<DeviceIdentity, MxError> {
+        match &self.identity {
+            Some(identity) => Ok(identity.clone()),
+            None => self.refresh_identity(),
+        }
+    }
+
+    /// Re-query the identity, `*IDN?`, of the instrument and update the cached value.
+    pub fn refresh_identity(&mut self) -> Result<DeviceIdentity, MxError> {
+        let reply = self._query_and_check("*IDN?")?;
+        // Reply format: "THURLBY THANDAR,MX100TP,123456,1.00-1.00-1.00"
+        let parts: Vec<&str> = reply.split(',').map(str::trim).collect();
+        if parts.len() == 4 {
+            let identity = DeviceIdentity {
+                manufacturer: parts[0].to_string(),
+                model: parts[1].to_string(),
+                serial: parts[2].to_string(),
+                firmware: parts[3].to_string(),
+            };
+            self.identity = Some(identity.clone());
+            Ok(identity)
+        } else {
+            Err(MxError::Parse(format!("Unexpected format for identify (*IDN?): '{}'", reply)))
+        }
     }
 
     /// Increment the current limit by step size of the output channel.
@@ -332,41 +1920,131 @@ impl MxSeries {
 
     /// Increment the voltage by step size of the output channel.
     pub fn increment_voltage(&mut self, channel: u8, verify: bool) -> Result<(), MxError> {
-        let command = format!("INCV{}{}", channel, if verify { "V" } else { "" });
+        let command = format!("INCV{}{}", channel, if verify || self.verify_default { "V" } else { "" });
         self._write_and_check(&command)
     }
 
+    /// Check if the output channel is in constant-current mode, i.e. the load is drawing the
+    /// current limit. This is normal regulation, not a trip.
+    ///
+    /// Reads only the current limit bit of the limit status register, so it is cheap enough
+    /// to poll during a ramp.
+    pub fn is_current_limited(&mut self, channel: u8) -> Result<bool, MxError> {
+        let lsr = self.limit_status_register(channel)?;
+        Ok(lsr & 0b00000010 != 0) // Bit 1 - Current limit (CC)
+    }
+
     /// Check if the output channel is on or off.
     pub fn is_output_on(&mut self, channel: u8) -> Result<bool, MxError> {
-        let reply = self._query_and_check(&format!("OP{}?", channel))?;
         // Reply format: "1" or "0"
-        match reply.trim() {
-            "1" => Ok(true),
-            "0" => Ok(false),
-            _ => Err(MxError::Parse(format!("Unexpected reply for is_output_on (OP{}?): '{}'", channel, reply))),
+        self.query_value(&format!("OP{}?", channel), parse::flag)
+    }
+
+    /// Check whether the instrument has a capability, based on its model.
+    ///
+    /// No MX Series model has list mode or slew limiting; all have remote sense, and the
+    /// "P" models (e.g. MX100TP) add a LAN interface.
+    pub fn supports(&mut self, feature: Feature) -> Result<bool, MxError> {
+        let identity = self.identify()?;
+        let known_model = MODEL_RANGES.contains_key(identity.model.as_str());
+        Ok(match feature {
+            Feature::ListMode | Feature::SlewLimiting => false,
+            Feature::RemoteSense => known_model,
+            Feature::Lan => known_model && identity.model.ends_with('P'),
+        })
+    }
+
+    /// The number of trips observed on each channel, as `(channel, ovp trips, ocp trips)`.
+    ///
+    /// Trips are only seen when `channel_trip_status` (or a method using it) is called; a trip
+    /// that occurs and is reset between two reads is not counted.
+    pub fn trip_counts(&self) -> Vec<(u8, u32, u32)> {
+        self.trip_history.iter()
+            .map(|(&channel, history)| (channel, history.ovp_trips, history.ocp_trips))
+            .collect()
+    }
+
+    /// Reset the counts reported by `trip_counts` to zero.
+    pub fn reset_trip_counts(&mut self) {
+        for history in self.trip_history.values_mut() {
+            history.ovp_trips = 0;
+            history.ocp_trips = 0;
         }
     }
 
+    /// The time since a new trip of the channel was last observed, or `None` if none has been.
+    ///
+    /// Like `trip_counts`, this is tracked by the crate from the trips seen by
+    /// `channel_trip_status`, so it is only as precise as the polling interval.
+    pub fn time_since_last_trip(&self, channel: u8) -> Option<Duration> {
+        self.trip_history.get(&channel)?.last_trip.map(|at| at.elapsed())
+    }
+
+    /// Send the trigger, `*TRG`, command.
+    ///
+    /// No arming is required on the MX Series; the trigger acts on whatever is pending.
+    pub fn trigger(&mut self) -> Result<(), MxError> {
+        self._write("*TRG")
+    }
+
+    /// Apply the Multi-On action and delay of each channel in `options`, without turning any
+    /// output on.
+    ///
+    /// Every channel is attempted even if an earlier one fails, and the outcome of each is
+    /// returned in channel order, so the caller can decide whether to go on with `OPALL 1`.
+    pub fn configure_multi_on(&mut self, options: HashMap<u8, MultiOperationConfig>) -> Vec<(u8, Result<(), MxError>)> {
+        self._configure_multi(options, Self::set_multi_on_action, Self::set_multi_on_delay)
+    }
+
+    /// Apply the Multi-Off action and delay of each channel in `options`, without turning any
+    /// output off. See `configure_multi_on`.
+    pub fn configure_multi_off(&mut self, options: HashMap<u8, MultiOperationConfig>) -> Vec<(u8, Result<(), MxError>)> {
+        self._configure_multi(options, Self::set_multi_off_action, Self::set_multi_off_delay)
+    }
+
+    fn _configure_multi(
+        &mut self,
+        options: HashMap<u8, MultiOperationConfig>,
+        set_action: fn(&mut Self, u8, MultiActionType) -> Result<(), MxError>,
+        set_delay: fn(&mut Self, u8, u16) -> Result<(), MxError>,
+    ) -> Vec<(u8, Result<(), MxError>)> {
+        let options: BTreeMap<_, _> = options.into_iter().collect();
+        options.into_iter()
+            .map(|(channel, config)| {
+                let result = match config {
+                    MultiOperationConfig::Action(enable_quick) => {
+                        set_action(self, channel, if enable_quick { MultiActionType::Quick } else { MultiActionType::Never })
+                    }
+                    MultiOperationConfig::DelayMs(ms) => {
+                        set_action(self, channel, MultiActionType::Delay)
+                            .and_then(|_| set_delay(self, channel, ms))
+                            .map(|_| thread::sleep(Duration::from_millis(100))) // As per Python code
+                    }
+                };
+                (channel, result)
+            })
+            .collect()
+    }
+
+    fn _first_multi_error(results: Vec<(u8, Result<(), MxError>)>) -> Result<(), MxError> {
+        results.into_iter().try_for_each(|(channel, result)| {
+            result.map_err(|e| MxError::MultiOp { channel, source: Box::new(e) })
+        })
+    }
+
     /// Turn the output channel on.
     pub fn turn_on(&mut self, channel: u8) -> Result<(), MxError> {
         self._write_and_check(&format!("OP{} 1", channel))
     }
 
     /// Turn multiple output channels on (the Multi-On feature).
+    ///
+    /// The options of every channel are applied before any output is switched. If a channel
+    /// failed, `OPALL` is not sent and `MxError::MultiOp` names the first such channel; use
+    /// `configure_multi_on` to see the outcome of each channel.
     pub fn turn_on_multi(&mut self, options: Option<HashMap<u8, MultiOperationConfig>>) -> Result<(), MxError> {
         if let Some(opts) = options {
-            for (channel, config) in opts {
-                match config {
-                    MultiOperationConfig::Action(enable_quick) => {
-                        self.set_multi_on_action(channel, if enable_quick { MultiActionType::Quick } else { MultiActionType::Never })?;
-                    }
-                    MultiOperationConfig::DelayMs(ms) => {
-                        self.set_multi_on_action(channel, MultiActionType::Delay)?;
-                        self.set_multi_on_delay(channel, ms)?;
-                        thread::sleep(Duration::from_millis(100)); // As per Python code
-                    }
-                }
-            }
+            Self::_first_multi_error(self.configure_multi_on(opts))?;
         }
         self._write_and_check("OPALL 1")
     }
@@ -377,29 +2055,243 @@ impl MxSeries {
     }
 
     /// Turn multiple output channels off (the Multi-Off feature).
+    ///
+    /// The options of every channel are applied before any output is switched. If a channel
+    /// failed, `OPALL` is not sent and `MxError::MultiOp` names the first such channel; use
+    /// `configure_multi_off` to see the outcome of each channel.
     pub fn turn_off_multi(&mut self, options: Option<HashMap<u8, MultiOperationConfig>>) -> Result<(), MxError> {
         if let Some(opts) = options {
-            for (channel, config) in opts {
-                match config {
-                    MultiOperationConfig::Action(enable_quick) => {
-                        self.set_multi_off_action(channel, if enable_quick { MultiActionType::Quick } else { MultiActionType::Never })?;
-                    }
-                    MultiOperationConfig::DelayMs(ms) => {
-                        self.set_multi_off_action(channel, MultiActionType::Delay)?;
-                        self.set_multi_off_delay(channel, ms)?;
-                        thread::sleep(Duration::from_millis(100)); // As per Python code
-                    }
-                }
-            }
+            Self::_first_multi_error(self.configure_multi_off(opts))?;
         }
         self._write_and_check("OPALL 0")
     }
 
+    /// Read the raw limit status register of the output channel.
+    ///
+    /// This is the bitmask that `channel_trip_status` and `operating_mode` decode; see the
+    /// `LSR<N>?` command in the manual for the meaning of each bit.
+    pub fn limit_status_register(&mut self, channel: u8) -> Result<u8, MxError> {
+        let val_str = self._query_and_check(&format!("LSR{}?", channel))?;
+        val_str.trim().parse::<u8>().map_err(|e| {
+            MxError::Parse(format!("Failed to parse LSR value '{}': {}", val_str, e))
+        })
+    }
+
+    /// Measure the output voltage and current of the output channel.
+    pub fn measure(&mut self, channel: u8) -> Result<ChannelMeasurement, MxError> {
+        Ok(ChannelMeasurement {
+            channel,
+            voltage: self.get_voltage(channel)?,
+            current: self.get_current(channel)?,
+        })
+    }
+
+    /// Measure the output voltage and current of every channel.
+    ///
+    /// All channels are read with a single batched query. If the instrument rejects batched
+    /// queries, each channel is measured individually instead, for this and all later calls.
+    pub fn measure_all(&mut self) -> Result<Vec<ChannelMeasurement>, MxError> {
+        let batched = self._batched_channel_query(
+            |n| format!("V{n}O?;I{n}O?", n = n),
+            |channel, lines| Ok(ChannelMeasurement {
+                channel,
+                voltage: parse::with_suffix('V')(&lines[0])?,
+                current: parse::with_suffix('A')(&lines[1])?,
+            }),
+        )?;
+        match batched {
+            Some(measurements) => Ok(measurements),
+            None => (1..=self.channel_count()?).map(|channel| self.measure(channel)).collect(),
+        }
+    }
+
+    /// Get the LAN settings of the instrument.
+    ///
+    /// Only the "P" models (e.g. MX100TP) have a LAN interface; other models return
+    /// `MxError::UnsupportedFeature`. The gateway and MAC address cannot be queried remotely,
+    /// see `NetworkConfig`.
+    pub fn network_config(&mut self) -> Result<NetworkConfig, MxError> {
+        if !self.supports(Feature::Lan)? {
+            return Err(MxError::UnsupportedFeature(format!("The {} has no LAN interface", self.identify()?.model)));
+        }
+        Ok(NetworkConfig {
+            ip_address: self._query_and_check("IPADDR?")?,
+            netmask: self._query_and_check("NETMASK?")?,
+            mode: self._query_and_check("NETCONFIG?")?,
+        })
+    }
+
+    /// Get the regulation mode of the output channel from its limit status register.
+    pub fn operating_mode(&mut self, channel: u8) -> Result<OperatingMode, MxError> {
+        let lsr = self.limit_status_register(channel)?;
+        Ok(Self::_decode_operating_mode(lsr))
+    }
+
+    /// Tell whether the output channel is on, off after a trip, or off without one.
+    ///
+    /// The trip status is only read if the output is off; a trip is counted in
+    /// `trip_counts` as by `channel_trip_status`.
+    pub fn output_off_reason(&mut self, channel: u8) -> Result<OffReason, MxError> {
+        if self.is_output_on(channel)? {
+            return Ok(OffReason::On);
+        }
+        Ok(if self.channel_trip_status(channel)?.is_tripped() {
+            OffReason::TrippedOff
+        } else {
+            OffReason::ManualOff
+        })
+    }
+
+    /// Read the standard event status register without losing its bits.
+    ///
+    /// The instrument has no non-destructive read, so `*ESR?` still clears the register.
+    /// The bits read are cached and merged into the next read that consumes the register:
+    /// the automatic status check after a command, or `event_status_register`. Repeated
+    /// peeks accumulate, and `clear` discards the cache along with the register.
+    pub fn peek_esr(&mut self) -> Result<u8, MxError> {
+        let val_str = self._query("*ESR?")?;
+        let value = val_str.trim().parse::<u8>().map_err(|e| {
+            MxError::Parse(format!("Failed to parse ESR value '{}': {}", val_str, e))
+        })?;
+        self.esr_pending |= value;
+        Ok(self.esr_pending)
+    }
+
+    /// Set the current limit and voltage of the output channel, then turn it on.
+    ///
+    /// Both setpoints are sent in one batched command and checked before the output is
+    /// enabled, so the output is never turned on with a rejected setting.
+    pub fn power_channel(&mut self, channel: u8, voltage: f32, current_limit: f32) -> Result<(), MxError> {
+        Self::_check_setpoint(&format!("I{}", channel), current_limit)?;
+        Self::_check_setpoint(&format!("V{}", channel), voltage)?;
+        self._write_and_check(&format!(
+            "I{channel} {current:.3};V{channel} {voltage:.3}",
+            channel = channel,
+            current = current_limit,
+            voltage = voltage,
+        ))?;
+        self.turn_on(channel)
+    }
+
+    /// Turn the channels off one at a time in the given order, waiting `delay_between` after
+    /// each, then set their voltages to zero.
+    ///
+    /// The cancel flag is checked before each channel; a cancelled sequence leaves the
+    /// remaining channels on and no voltage zeroed.
+    pub fn power_down_sequence(&mut self, order: &[u8], delay_between: Duration) -> Result<(), MxError> {
+        for (i, &channel) in order.iter().enumerate() {
+            self._check_cancelled()?;
+            self.turn_off(channel)?;
+            if i + 1 < order.len() {
+                thread::sleep(delay_between);
+            }
+        }
+        for &channel in order {
+            self.set_voltage(channel, 0.0, false)?;
+        }
+        Ok(())
+    }
+
+    /// Get the over-voltage and over-current protection trip points of every channel.
+    ///
+    /// Returns `(channel, ovp, ocp)` per channel, where `None` means that protection is
+    /// disabled. All channels are read with a single batched query, falling back to
+    /// `get_over_voltage_protection` and `get_over_current_protection` per channel like
+    /// `measure_all`.
+    pub fn protection_settings(&mut self) -> Result<Vec<ProtectionSetting>, MxError> {
+        let batched = self._batched_channel_query(
+            |n| format!("OVP{n}?;OCP{n}?", n = n),
+            |channel, lines| Ok((
+                channel,
+                parse::optional_value_token(&lines[0])?,
+                parse::optional_value_token(&lines[1])?,
+            )),
+        )?;
+        match batched {
+            Some(settings) => Ok(settings),
+            None => (1..=self.channel_count()?)
+                .map(|channel| Ok((
+                    channel,
+                    self.get_over_voltage_protection(channel)?,
+                    self.get_over_current_protection(channel)?,
+                )))
+                .collect(),
+        }
+    }
+
+    /// The current limit the instrument will store for `value` on the output channel.
+    ///
+    /// See `quantize_voltage`; the same rules apply to the current ranges.
+    pub fn quantize_current(&self, channel: u8, value: f32) -> f32 {
+        self._quantize(channel, value, |&(_, max_current)| max_current)
+    }
+
+    /// The voltage the instrument will store for `value` on the output channel, without any
+    /// communication.
+    ///
+    /// `value` is rounded to the setpoint resolution of the channel's range and to the three
+    /// decimals setpoints are sent with. The range is the one last read or set with
+    /// `get_voltage_range`, `set_voltage_range` or `get_setpoints`, looked up in the identity
+    /// cached by `identify`; if the range is unknown, the lowest range that can produce
+    /// `value` is assumed. Without a cached identity only the three decimals apply.
+    pub fn quantize_voltage(&self, channel: u8, value: f32) -> f32 {
+        self._quantize(channel, value, |&(max_voltage, _)| max_voltage)
+    }
+
+    /// Send a query and parse its reply with `parser`, checking for errors like the built-in getters.
+    ///
+    /// The `parse` module has parsers for the reply formats used by the instrument. Parse
+    /// errors are annotated with the command that was sent.
+    pub fn query_value<T>(&mut self, command: &str, parser: impl Fn(&str) -> Result<T, MxError>) -> Result<T, MxError> {
+        let reply = self._query_and_check(command)?;
+        parser(&reply).map_err(|e| Self::_with_context(e, command))
+    }
+
+    /// Send a query that produces several reply lines and read exactly `expected` of them.
+    ///
+    /// Use this for batched queries such as `V1?;V2?`, whose replies would otherwise be left
+    /// buffered and corrupt the next read.
+    pub fn query_lines(&mut self, cmd: &str, expected: usize) -> Result<Vec<String>, MxError> {
+        if self.dry_run {
+            return Err(Self::_dry_run_error(cmd));
+        }
+        let started = Instant::now();
+        let read = self._send(cmd)
+            .and_then(|_| self._discard_echoes())
+            .and_then(|_| self.connection.read_lines(expected))
+            // An empty line means the read timed out before the reply arrived, e.g. because
+            // the instrument rejected the query, which the status register then tells.
+            .and_then(|lines| match lines.iter().position(|line| line.is_empty()) {
+                Some(missing) => Err(MxError::Parse(format!(
+                    "Expected {} reply lines for '{}', got {}", expected, cmd, missing
+                ))),
+                None => Ok(lines),
+            });
+        // The log and the command hook get the replies joined into one outcome.
+        let (lines, outcome) = match read {
+            Ok(lines) => {
+                let joined = lines.join(";");
+                (lines, Ok(joined))
+            }
+            Err(e) => (Vec::new(), Err(e)),
+        };
+        self._record_command(cmd, &outcome, started);
+        match outcome {
+            Ok(_) => Ok(lines),
+            Err(e) if !self.esr_check => Err(e),
+            Err(e) => match self._check_event_status_register(cmd) {
+                Ok(_) => Err(e),
+                Err(esr_err) => Err(esr_err),
+            },
+        }
+    }
+
     /// Recall the settings of the output channel from the store.
     pub fn recall(&mut self, channel: u8, index: u8) -> Result<(), MxError> {
         if index > 49 {
             return Err(MxError::InvalidParameter("Store index must be 0-49.".to_string()));
         }
+        self.voltage_ranges.remove(&channel);
         self._write_and_check(&format!("RCL{} {}", channel, index))
     }
 
@@ -413,22 +2305,69 @@ impl MxSeries {
         // Manual for MX100TP: "*SAV n Saves settings of all outputs to store n."
         // The Python code seems to have a typo here, using *SAV for recall_all.
         // Correcting to *RCL for recall_all.
+        self.voltage_ranges.clear();
         self._write_and_check(&format!("*RCL {}", index))
     }
 
-    /// Send the reset, `*RST`, command.
+    /// Send the reset, `*RST`, command and wait for the instrument to complete it.
+    ///
+    /// Completion is awaited with a single `*OPC?`, which the instrument answers once the
+    /// reset is done; its reply is read until the reset timeout has passed, so the timeout
+    /// should exceed the read timeout. Fails with `MxError::Timeout` if no reply has arrived
+    /// by then.
     pub fn reset(&mut self) -> Result<(), MxError> {
-        self.connection.write_command("*RST")?;
-        // *RST can take some time. A delay might be prudent before subsequent commands.
-        thread::sleep(Duration::from_millis(500)); // Adjust as needed
-        Ok(())
+        self.voltage_ranges.clear();
+        self._write("*RST")?;
+        if self.dry_run {
+            return Ok(());
+        }
+        let start = Instant::now();
+        // Sending *OPC? again would leave an extra "1" buffered for each query sent.
+        let mut reply = self._query("*OPC?");
+        loop {
+            match reply {
+                Ok(ref reply) if reply.trim() == "1" => return Ok(()),
+                // No reply yet: sockets fail with a timeout, serial reads come back empty.
+                Ok(ref reply) if reply.trim().is_empty() => {}
+                Err(MxError::Io(_)) => {}
+                Ok(reply) => return Err(MxError::Parse(format!("Unexpected reply to *OPC?: '{}'", reply))),
+                Err(e) => return Err(e),
+            }
+            if start.elapsed() >= self.reset_timeout {
+                return Err(MxError::Timeout(format!("Reset did not complete within {:?}", self.reset_timeout)));
+            }
+            reply = self._read_late_reply("*OPC?");
+        }
     }
 
     /// Attempt to clear all trip conditions.
+    ///
+    /// The trips are not re-read afterwards; use `reset_trips_verified` to confirm that a
+    /// latching fault has actually cleared.
     pub fn reset_trip(&mut self) -> Result<(), MxError> {
         self._write_and_check("TRIPRST")
     }
 
+    /// Clear all trip conditions and confirm the tripped channels recovered.
+    ///
+    /// Returns the channels that were tripped before the reset. Fails with
+    /// `MxError::TripPersists` if any of them is still tripped afterwards.
+    pub fn reset_trips_verified(&mut self) -> Result<Vec<u8>, MxError> {
+        let mut tripped = Vec::new();
+        for channel in 1..=self.channel_count()? {
+            if self.channel_trip_status(channel)?.is_tripped() {
+                tripped.push(channel);
+            }
+        }
+        self.reset_trip()?;
+        for &channel in &tripped {
+            if self.channel_trip_status(channel)?.is_tripped() {
+                return Err(MxError::TripPersists { channel });
+            }
+        }
+        Ok(tripped)
+    }
+
     /// Save the present settings of the output channel to the store.
     pub fn save(&mut self, channel: u8, index: u8) -> Result<(), MxError> {
         if index > 49 {
@@ -447,11 +2386,62 @@ impl MxSeries {
         self._write_and_check(&format!("*SAV {}", index))
     }
 
+    /// Run `set`, read the result back with `get` and check that it equals `expected`.
+    ///
+    /// Returns `MxError::ConfirmationMismatch` if it does not. Values are compared exactly, so
+    /// for measured or rounded floats compare within a tolerance instead.
+    pub fn set_and_verify<T: PartialEq + fmt::Debug>(
+        &mut self,
+        set: impl FnOnce(&mut Self) -> Result<(), MxError>,
+        get: impl FnOnce(&mut Self) -> Result<T, MxError>,
+        expected: T,
+    ) -> Result<(), MxError> {
+        set(self)?;
+        let actual = get(self)?;
+        if actual != expected {
+            return Err(MxError::ConfirmationMismatch {
+                setting: "read-back value".to_string(),
+                expected: format!("{:?}", expected),
+                actual: format!("{:?}", actual),
+            });
+        }
+        Ok(())
+    }
+
     /// Set the current limit of the output channel.
     pub fn set_current_limit(&mut self, channel: u8, value: f32) -> Result<(), MxError> {
+        Self::_check_setpoint(&format!("I{}", channel), value)?;
         self._write_and_check(&format!("I{} {:.3}", channel, value))
     }
 
+    /// Set the current limit of the output channel, switching voltage range first if needed.
+    ///
+    /// If `value` exceeds the maximum current of the active range, the range with the highest
+    /// voltage that can supply `value` is selected. Changing range requires the output to be
+    /// below 0.5V; otherwise this fails with the same `RangeChangeError` (code 104) the
+    /// instrument would report, without changing anything.
+    pub fn set_current_limit_auto_range(&mut self, channel: u8, value: f32) -> Result<(), MxError> {
+        if value <= self._active_range(channel)?.max_current {
+            return self.set_current_limit(channel, value);
+        }
+
+        let ranges = self._channel_ranges(channel)?;
+        let target = ranges.iter()
+            .enumerate()
+            .filter(|(_, &(_, max_current))| value <= max_current)
+            .max_by(|(_, a), (_, b)| a.0.total_cmp(&b.0))
+            .map(|(i, _)| i as i32 + 1)
+            .ok_or_else(|| MxError::InvalidParameter(format!(
+                "Current limit {:.3}A exceeds every range of channel {}.", value, channel
+            )))?;
+        let command = format!("VRANGE{} {}", channel, target);
+        if self.get_voltage(channel)? > 0.5 {
+            return Err(Self::_execution_error(104, &command));
+        }
+        self.set_voltage_range(channel, target)?;
+        self.set_current_limit(channel, value)
+    }
+
     /// Set the current meter measurement averaging of the output channel.
     pub fn set_current_meter_averaging(&mut self, channel: u8, value: MeterAveraging) -> Result<(), MxError> {
         self._write_and_check(&format!("DAMPING{} {}", channel, value.as_str()))
@@ -459,17 +2449,13 @@ impl MxSeries {
 
     /// Set the current limit step size of the output channel.
     pub fn set_current_step_size(&mut self, channel: u8, size: f32) -> Result<(), MxError> {
+        Self::_check_setpoint(&format!("DELTAI{}", channel), size)?;
         self._write_and_check(&format!("DELTAI{} {:.3}", channel, size))
     }
 
     /// Set the Multi-On action of the output channel.
     pub fn set_multi_on_action(&mut self, channel: u8, action: MultiActionType) -> Result<(), MxError> {
-        let action_str = match action {
-            MultiActionType::Quick => "QUICK",
-            MultiActionType::Never => "NEVER",
-            MultiActionType::Delay => "DELAY",
-        };
-        self._write_and_check(&format!("ONACTION{} {}", channel, action_str))
+        self._write_and_check(&format!("ONACTION{} {}", channel, action.as_str()))
     }
 
     /// Set the Multi-On delay, in milliseconds, of the output channel.
@@ -479,12 +2465,7 @@ impl MxSeries {
 
     /// Set the Multi-Off action of the output channel.
     pub fn set_multi_off_action(&mut self, channel: u8, action: MultiActionType) -> Result<(), MxError> {
-        let action_str = match action {
-            MultiActionType::Quick => "QUICK",
-            MultiActionType::Never => "NEVER",
-            MultiActionType::Delay => "DELAY",
-        };
-        self._write_and_check(&format!("OFFACTION{} {}", channel, action_str))
+        self._write_and_check(&format!("OFFACTION{} {}", channel, action.as_str()))
     }
 
     /// Set the Multi-Off delay, in milliseconds, of the output channel.
@@ -493,34 +2474,57 @@ impl MxSeries {
     }
 
     /// Set the over-current protection trip point of the output channel.
+    ///
+    /// When enabling, protection is switched on and the trip point set as two separately checked
+    /// commands. If the instrument rejects the trip point, `MxError::ProtectionValueRejected` is
+    /// returned; otherwise the trip point is read back and `MxError::ConfirmationMismatch` is
+    /// returned if it was not applied.
     pub fn set_over_current_protection(&mut self, channel: u8, enable: bool, value: Option<f32>) -> Result<(), MxError> {
-        let command = if enable {
-            match value {
-                Some(val) => format!("OCP{channel} ON;OCP{channel} {value:.3}", channel=channel, value=val),
-                None => return Err(MxError::InvalidParameter("Must specify OCP value if enabling.".to_string())),
-            }
-        } else {
-            format!("OCP{} OFF", channel)
-        };
-        self._write_and_check(&command)
+        self._set_protection("OCP", channel, enable, value, Self::get_over_current_protection)
+    }
+
+    /// Set the over-voltage protection trip point of the output channel as a percentage of its
+    /// present voltage setpoint, e.g. 110.0 for 10% above it.
+    ///
+    /// Percentages below 100 would trip immediately and are rejected.
+    pub fn set_ovp_percent(&mut self, channel: u8, percent: f32) -> Result<(), MxError> {
+        if !(percent >= 100.0 && percent.is_finite()) {
+            return Err(MxError::InvalidParameter(format!("OVP percentage must be at least 100, got {}.", percent)));
+        }
+        let setpoint = self.get_voltage_setpoint(channel)?;
+        if setpoint <= 0.0 {
+            return Err(MxError::InvalidParameter(format!("Voltage setpoint of channel {} is zero.", channel)));
+        }
+        self.set_over_voltage_protection(channel, true, Some(setpoint * percent / 100.0))
     }
 
     /// Set the over-voltage protection trip point of the output channel.
+    ///
+    /// When enabling, protection is switched on and the trip point set as two separately checked
+    /// commands. If the instrument rejects the trip point, `MxError::ProtectionValueRejected` is
+    /// returned; otherwise the trip point is read back and `MxError::ConfirmationMismatch` is
+    /// returned if it was not applied.
     pub fn set_over_voltage_protection(&mut self, channel: u8, enable: bool, value: Option<f32>) -> Result<(), MxError> {
-        let command = if enable {
-            match value {
-                Some(val) => format!("OVP{channel} ON;OVP{channel} {value:.3}", channel=channel, value=val),
-                None => return Err(MxError::InvalidParameter("Must specify OVP value if enabling.".to_string())),
+        self._set_protection("OVP", channel, enable, value, Self::get_over_voltage_protection)
+    }
+
+    /// Set both the over-voltage and over-current protection of the output channel in one
+    /// batched command with a single status check. `None` disables that protection.
+    ///
+    /// Unlike `set_over_voltage_protection`, the trip points are not read back.
+    pub fn set_protection(&mut self, channel: u8, ovp: Option<f32>, ocp: Option<f32>) -> Result<(), MxError> {
+        for (kind, value) in [("OVP", ovp), ("OCP", ocp)] {
+            if let Some(value) = value {
+                Self::_check_setpoint(&format!("{}{}", kind, channel), value)?;
             }
-        } else {
-            format!("OVP{} OFF", channel)
-        };
-        self._write_and_check(&command)
+        }
+        self._write_and_check(&Self::_protection_clauses(channel, ovp, ocp))
     }
 
     /// Set the output voltage of the output channel.
     pub fn set_voltage(&mut self, channel: u8, value: f32, verify: bool) -> Result<(), MxError> {
-        let command = if verify {
+        Self::_check_setpoint(&format!("V{}", channel), value)?;
+        let command = if verify || self.verify_default {
             format!("V{}V {:.3}", channel, value)
         } else {
             format!("V{} {:.3}", channel, value)
@@ -530,11 +2534,30 @@ impl MxSeries {
 
     /// Set the output voltage range of the output channel.
     pub fn set_voltage_range(&mut self, channel: u8, index: i32) -> Result<(), MxError> {
-        self._write_and_check(&format!("VRANGE{} {}", channel, index))
+        // Forget the range first, in case the command fails after the instrument applied it.
+        self.voltage_ranges.remove(&channel);
+        self._write_and_check(&format!("VRANGE{} {}", channel, index))?;
+        self.voltage_ranges.insert(channel, index);
+        Ok(())
+    }
+
+    /// Set the output voltage of the output channel to the point of its step grid nearest to
+    /// `value`, and return the voltage applied.
+    ///
+    /// The grid is the step size reported by `get_voltage_step_size`, counted from zero.
+    pub fn set_voltage_snapped(&mut self, channel: u8, value: f32, verify: bool) -> Result<f32, MxError> {
+        let step = self.get_voltage_step_size(channel)?;
+        if step <= 0.0 {
+            return Err(MxError::InvalidParameter(format!("Voltage step size of channel {} is zero.", channel)));
+        }
+        let snapped = (value / step).round() * step;
+        self.set_voltage(channel, snapped, verify)?;
+        Ok(snapped)
     }
 
     /// Set the voltage step size of the output channel.
     pub fn set_voltage_step_size(&mut self, channel: u8, size: f32) -> Result<(), MxError> {
+        Self::_check_setpoint(&format!("DELTAV{}", channel), size)?;
         self._write_and_check(&format!("DELTAV{} {:.3}", channel, size))
     }
 
@@ -542,4 +2565,194 @@ impl MxSeries {
     pub fn set_voltage_tracking_mode(&mut self, mode: i32) -> Result<(), MxError> {
         self._write_and_check(&format!("CONFIG {}", mode))
     }
+
+    /// Turn the output channel off, then set its voltage to zero.
+    pub fn unpower_channel(&mut self, channel: u8) -> Result<(), MxError> {
+        self.turn_off(channel)?;
+        self.set_voltage(channel, 0.0, false)
+    }
+
+    /// Check that a store holds the expected settings for the output channel.
+    ///
+    /// The instrument cannot read a store without recalling it, so the store is recalled into
+    /// the live settings, read back and compared with `expected` within the verify tolerance,
+    /// and the previous settings are then written back. Because recalling changes the live
+    /// settings, the output must be off; otherwise `MxError::InvalidParameter` is returned.
+    pub fn verify_store(&mut self, channel: u8, index: u8, expected: &ChannelConfig) -> Result<bool, MxError> {
+        if self.is_output_on(channel)? {
+            return Err(MxError::InvalidParameter(format!("Output {} must be off to verify a store.", channel)));
+        }
+        let previous = self._read_channel_config(channel)?;
+        let stored = self.recall(channel, index).and_then(|_| self._read_channel_config(channel));
+        let restored = self._write_channel_config(channel, &previous);
+        let stored = stored?;
+        restored?;
+        Ok(stored.matches(expected, self.verify_epsilon))
+    }
+
+    /// Get the voltage setpoint and measured output voltage of the output channel, and the
+    /// regulation error: measured minus setpoint.
+    ///
+    /// A large negative error means the output has been pulled out of voltage regulation,
+    /// typically into constant-current mode.
+    pub fn voltage_regulation(&mut self, channel: u8) -> Result<(f32, f32, f32), MxError> {
+        let setpoint = self.get_voltage_setpoint(channel)?;
+        let measured = self.get_voltage(channel)?;
+        Ok((setpoint, measured, measured - setpoint))
+    }
+
+    /// Wait until the output channel is no longer tripped, e.g. after reducing the load that
+    /// caused an over-current trip.
+    ///
+    /// With `set_reset_trip_on_wait` enabled, `TRIPRST` is sent once first. The limit status
+    /// register is then polled until no trip bit is set; fails with `MxError::Timeout` if the
+    /// trip persists beyond `timeout`, or with `MxError::Cancelled` if the cancel flag is set.
+    pub fn wait_trip_clear(&mut self, channel: u8, timeout: Duration) -> Result<(), MxError> {
+        if self.reset_trip_on_wait {
+            self.reset_trip()?;
+        }
+        let start = Instant::now();
+        loop {
+            self._check_cancelled()?;
+            if !self.channel_trip_status(channel)?.is_tripped() {
+                return Ok(());
+            }
+            if start.elapsed() >= timeout {
+                return Err(MxError::Timeout(format!("Trip on channel {} did not clear within {:?}", channel, timeout)));
+            }
+            thread::sleep(COMMAND_DELAY);
+        }
+    }
+
+    /// Wait for the output current of the output channel to settle.
+    ///
+    /// The current is sampled every command delay until the last `window` samples are all
+    /// within `tolerance` of each other, and their mean is returned. Fails with
+    /// `MxError::Timeout` if that does not happen within `timeout`, or `MxError::Cancelled`
+    /// if the cancel flag is set.
+    pub fn wait_until_current_stable(&mut self, channel: u8, tolerance: f32, window: usize, timeout: Duration) -> Result<f32, MxError> {
+        if window == 0 {
+            return Err(MxError::InvalidParameter("Window must be at least one sample.".to_string()));
+        }
+        let start = Instant::now();
+        let mut samples = VecDeque::with_capacity(window);
+        loop {
+            self._check_cancelled()?;
+            if samples.len() == window {
+                samples.pop_front();
+            }
+            samples.push_back(self.get_current(channel)?);
+            if samples.len() == window {
+                let min = samples.iter().copied().fold(f32::INFINITY, f32::min);
+                let max = samples.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+                if max - min <= tolerance {
+                    return Ok(samples.iter().sum::<f32>() / window as f32);
+                }
+            }
+            if start.elapsed() >= timeout {
+                return Err(MxError::Timeout(format!("Current of channel {} did not settle within {:?}", channel, timeout)));
+            }
+            thread::sleep(COMMAND_DELAY);
+        }
+    }
+}
+
+/// Summarizes the instrument from cached state only, so it never talks to the instrument,
+/// e.g. "MX100TP (serial 123456) via socket 192.168.0.10:9221".
+impl fmt::Display for MxSeries {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.identity {
+            Some(identity) => write!(f, "{} (serial {})", identity.model, identity.serial)?,
+            None => write!(f, "unknown model")?,
+        }
+        match &self.endpoint {
+            Some(endpoint) => write!(f, " via {}", endpoint),
+            None => write!(f, " via custom connection"),
+        }
+    }
+}
+
+impl fmt::Debug for MxSeries {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MxSeries")
+            .field("endpoint", &self.endpoint)
+            .field("identity", &self.identity)
+            .field("timeout", &self.timeout)
+            .field("write_char_delay", &self.write_char_delay)
+            .field("dry_run", &self.dry_run)
+            .field("esr_check", &self.esr_check)
+            .field("compound_queries", &self.compound_queries)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Keeps the automatic status check disabled while alive. Returned by `MxSeries::esr_check_paused`.
+pub struct EsrCheckGuard<'a> {
+    psu: &'a mut MxSeries,
+    previous: bool,
+}
+
+impl Deref for EsrCheckGuard<'_> {
+    type Target = MxSeries;
+
+    fn deref(&self) -> &MxSeries {
+        self.psu
+    }
+}
+
+impl DerefMut for EsrCheckGuard<'_> {
+    fn deref_mut(&mut self) -> &mut MxSeries {
+        self.psu
+    }
+}
+
+impl Drop for EsrCheckGuard<'_> {
+    fn drop(&mut self) {
+        self.psu.esr_check = self.previous;
+    }
+}
+
+/// Holds the interface lock while alive. Returned by `MxSeries::acquire_lock`.
+pub struct InterfaceLock<'a> {
+    psu: &'a mut MxSeries,
+    released: bool,
+}
+
+impl InterfaceLock<'_> {
+    /// Releases the lock now, reporting whether the instrument accepted `IFUNLOCK`.
+    pub fn release(mut self) -> Result<(), MxError> {
+        self.released = true;
+        Self::_unlock(self.psu)
+    }
+
+    fn _unlock(psu: &mut MxSeries) -> Result<(), MxError> {
+        // Reply format: "0" if released, "-1" if not
+        let reply: i32 = psu.query_value("IFUNLOCK", parse::whole)?;
+        if reply != 0 {
+            return Err(MxError::CommandError("Interface lock could not be released".to_string()));
+        }
+        Ok(())
+    }
+}
+
+impl Deref for InterfaceLock<'_> {
+    type Target = MxSeries;
+
+    fn deref(&self) -> &MxSeries {
+        self.psu
+    }
+}
+
+impl DerefMut for InterfaceLock<'_> {
+    fn deref_mut(&mut self) -> &mut MxSeries {
+        self.psu
+    }
+}
+
+impl Drop for InterfaceLock<'_> {
+    fn drop(&mut self) {
+        if !self.released {
+            let _ = Self::_unlock(self.psu);
+        }
+    }
 }
@@ -0,0 +1,53 @@
+//! Parsers for instrument replies, for use with `MxSeries::query_value`.
+//!
+//! Numbers are parsed with `FromStr`, so float values may use scientific notation such as
+//! `1.234E-01`, as some firmware replies.
+
+use crate::error::MxError;
+use std::str::FromStr;
+
+fn unexpected(reply: &str) -> MxError {
+    MxError::Parse(format!("Unexpected format: '{}'", reply))
+}
+
+/// Parses the whole reply, e.g. `"1"`.
+pub fn whole<T: FromStr>(reply: &str) -> Result<T, MxError> {
+    reply.trim().parse::<T>().map_err(|_| unexpected(reply))
+}
+
+/// Parses a reply consisting of a value and a unit suffix, e.g. `"5.000V"`.
+pub fn with_suffix<T: FromStr>(suffix: char) -> impl Fn(&str) -> Result<T, MxError> {
+    move |reply| match reply.trim().strip_suffix(suffix) {
+        Some(value) => value.parse::<T>().map_err(|_| unexpected(reply)),
+        None => Err(unexpected(reply)),
+    }
+}
+
+/// Parses the value of a `<header> <value>` reply, e.g. `"V1 5.000"`.
+pub fn value_token<T: FromStr>(reply: &str) -> Result<T, MxError> {
+    let parts: Vec<&str> = reply.split_whitespace().collect();
+    if parts.len() == 2 {
+        parts[1].parse::<T>().map_err(|_| unexpected(reply))
+    } else {
+        Err(unexpected(reply))
+    }
+}
+
+/// Parses a `<header> <value>` reply whose value may be `OFF`, e.g. `"OVP1 30.50"` or
+/// `"OVP1 OFF"`. `OFF` is returned as `None`.
+pub fn optional_value_token<T: FromStr>(reply: &str) -> Result<Option<T>, MxError> {
+    if reply.trim().to_uppercase().ends_with("OFF") {
+        Ok(None)
+    } else {
+        value_token(reply).map(Some)
+    }
+}
+
+/// Parses a `1` or `0` reply.
+pub fn flag(reply: &str) -> Result<bool, MxError> {
+    match reply.trim() {
+        "1" => Ok(true),
+        "0" => Ok(false),
+        _ => Err(unexpected(reply)),
+    }
+}
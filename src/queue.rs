@@ -0,0 +1,93 @@
+use crate::error::MxError;
+use crate::MxSeries;
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+/// A setpoint that `CommandQueue` coalesces.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Quantity {
+    Voltage,
+    CurrentLimit,
+}
+
+/// Buffers setpoint changes and only sends the latest value per channel and quantity.
+///
+/// Intended for rapidly changing inputs such as a UI slider: queue every change with
+/// `set_voltage`/`set_current_limit`, and call `poll` from the event loop to send the pending
+/// values once they have stopped changing for the debounce interval.
+pub struct CommandQueue {
+    psu: MxSeries,
+    debounce: Duration,
+    pending: BTreeMap<(u8, Quantity), f32>,
+    last_change: Option<Instant>,
+}
+
+impl CommandQueue {
+    /// Creates a queue in front of `psu` with the given debounce interval.
+    pub fn new(psu: MxSeries, debounce: Duration) -> Self {
+        CommandQueue {
+            psu,
+            debounce,
+            pending: BTreeMap::new(),
+            last_change: None,
+        }
+    }
+
+    /// Queues a new output voltage for the channel, replacing any pending one.
+    pub fn set_voltage(&mut self, channel: u8, value: f32) {
+        self.queue(channel, Quantity::Voltage, value);
+    }
+
+    /// Queues a new current limit for the channel, replacing any pending one.
+    pub fn set_current_limit(&mut self, channel: u8, value: f32) {
+        self.queue(channel, Quantity::CurrentLimit, value);
+    }
+
+    fn queue(&mut self, channel: u8, quantity: Quantity, value: f32) {
+        self.pending.insert((channel, quantity), value);
+        self.last_change = Some(Instant::now());
+    }
+
+    /// The number of values waiting to be sent.
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Sends the pending values if none has changed for the debounce interval.
+    ///
+    /// Returns `true` if anything was sent.
+    pub fn poll(&mut self) -> Result<bool, MxError> {
+        match self.last_change {
+            Some(last_change) if last_change.elapsed() >= self.debounce => {
+                self.flush()?;
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    /// Sends all pending values now.
+    ///
+    /// If a command fails, it and the values not yet sent stay queued.
+    pub fn flush(&mut self) -> Result<(), MxError> {
+        while let Some((&(channel, quantity), &value)) = self.pending.iter().next() {
+            match quantity {
+                Quantity::Voltage => self.psu.set_voltage(channel, value, false)?,
+                Quantity::CurrentLimit => self.psu.set_current_limit(channel, value)?,
+            }
+            self.pending.remove(&(channel, quantity));
+        }
+        self.last_change = None;
+        Ok(())
+    }
+
+    /// The power supply behind the queue, for commands that are not queued.
+    pub fn psu_mut(&mut self) -> &mut MxSeries {
+        &mut self.psu
+    }
+
+    /// Returns the power supply, discarding any pending values.
+    pub fn into_inner(self) -> MxSeries {
+        self.psu
+    }
+}
@@ -0,0 +1,48 @@
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::thread::{self, JoinHandle};
+
+/// A step of a scripted exchange: the command the instrument expects and its reply, if any.
+pub type Step = (&'static str, Option<&'static str>);
+
+/// A fake instrument on localhost that accepts one connection and plays back a script.
+pub struct ScriptedServer {
+    pub address: String,
+    handle: JoinHandle<()>,
+}
+
+impl ScriptedServer {
+    pub fn start(script: Vec<Step>) -> Self {
+        Self::start_with_terminator(script, "\n")
+    }
+
+    /// Like `start`, but ends each reply with `terminator` instead of LF.
+    pub fn start_with_terminator(script: Vec<Step>, terminator: &'static str) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind scripted server");
+        let address = listener.local_addr().unwrap().to_string();
+        let handle = thread::spawn(move || {
+            let (stream, _) = listener.accept().expect("accept client");
+            let mut writer = stream.try_clone().unwrap();
+            let mut reader = BufReader::new(stream);
+            for (expected, reply) in script {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                assert_eq!(line.trim_end(), expected, "unexpected command");
+                if let Some(reply) = reply {
+                    writer.write_all(format!("{}{}", reply, terminator).as_bytes()).unwrap();
+                }
+            }
+            let mut rest = String::new();
+            reader.read_line(&mut rest).unwrap();
+            assert!(rest.is_empty(), "unexpected command after script: {:?}", rest.trim_end());
+        });
+        ScriptedServer { address, handle }
+    }
+
+    /// Waits for the script to complete. The client must be dropped first.
+    pub fn finish(self) {
+        if let Err(panic) = self.handle.join() {
+            std::panic::resume_unwind(panic);
+        }
+    }
+}
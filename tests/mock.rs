@@ -0,0 +1,391 @@
+use mxpsu::connection::{Connection, ConnectionKind, MockConnection, RetryingConnection};
+use mxpsu::error::MxError;
+use mxpsu::{
+    ChannelConfig, InstrumentProfile, MultiOperationConfig, MxSeries, OffReason, RetryPolicy,
+};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+fn mock_psu(replies: &[&str]) -> MxSeries {
+    let mut mock = MockConnection::new();
+    for reply in replies {
+        mock.push_reply(reply);
+    }
+    MxSeries::from_connection(Box::new(mock))
+}
+
+fn written(psu: &mut MxSeries) -> Vec<Vec<u8>> {
+    psu.connection_downcast_mut::<MockConnection>().unwrap().written_bytes().to_vec()
+}
+
+#[test]
+fn set_voltage_writes_exact_bytes() {
+    let mut psu = mock_psu(&["0"]);
+    psu.set_voltage(1, 5.0, false).unwrap();
+    assert_eq!(written(&mut psu), vec![b"V1 5.000\n".to_vec(), b"*ESR?\n".to_vec()]);
+}
+
+#[test]
+fn write_command_raw_adds_no_terminator() {
+    let mut psu = mock_psu(&[]);
+    psu.write_command_raw(b"*RST").unwrap();
+    assert_eq!(written(&mut psu), vec![b"*RST".to_vec()]);
+}
+
+#[test]
+fn numeric_getters_accept_scientific_notation() {
+    let mut psu = mock_psu(&[
+        "1.234E-01A",
+        "5.000E+00V",
+        "I1 1.500E-01",
+        "V1 1.200E+01",
+        "OVP1 1.320E+01",
+        "OCP1 2.000E-01",
+        "DELTAV1 1.0E-02",
+    ]);
+    assert_eq!(psu.get_current(1).unwrap(), 0.1234);
+    assert_eq!(psu.get_voltage(1).unwrap(), 5.0);
+    assert_eq!(psu.get_current_limit(1).unwrap(), 0.15);
+    assert_eq!(psu.get_voltage_setpoint(1).unwrap(), 12.0);
+    assert_eq!(psu.get_over_voltage_protection(1).unwrap(), Some(13.2));
+    assert_eq!(psu.get_over_current_protection(1).unwrap(), Some(0.2));
+    assert_eq!(psu.get_voltage_step_size(1).unwrap(), 0.01);
+}
+
+#[test]
+fn apply_profile_batches_each_channel_and_checks_once() {
+    let mut psu = mock_psu(&["0"]);
+    let mut profile = InstrumentProfile { tracking_mode: Some(0), ..Default::default() };
+    profile.channels.insert(2, ChannelConfig {
+        voltage: 12.0,
+        current_limit: 0.5,
+        over_voltage_protection: Some(13.0),
+        over_current_protection: None,
+    });
+    psu.apply_profile(&profile).unwrap();
+    assert_eq!(
+        written(&mut psu),
+        vec![
+            b"CONFIG 0\n".to_vec(),
+            b"V2 12.000;I2 0.500;OVP2 ON;OVP2 13.000;OCP2 OFF\n".to_vec(),
+            b"*ESR?\n".to_vec(),
+        ]
+    );
+}
+
+#[test]
+fn garbled_eer_reply_keeps_command_context() {
+    let mut psu = mock_psu(&["16", "#?", "#?"]);
+    match psu.set_voltage(1, 99.0, false) {
+        Err(MxError::Parse(message)) => {
+            assert!(message.contains("'#?'"), "{}", message);
+            assert!(message.contains("V1 99.000"), "{}", message);
+        }
+        other => panic!("expected parse error, got {:?}", other),
+    }
+}
+
+#[test]
+fn interface_lock_is_released_on_drop() {
+    let mut psu = mock_psu(&["1", "0"]);
+    {
+        let _lock = psu.acquire_lock().unwrap();
+    }
+    assert_eq!(written(&mut psu), vec![b"IFLOCK\n".to_vec(), b"IFUNLOCK\n".to_vec()]);
+}
+
+#[test]
+fn echo_mode_discards_echoed_commands() {
+    let mut psu = mock_psu(&["V1 5.000", "*ESR?", "0", "V1O?", "5.000V"]);
+    psu.set_echo_mode(true);
+    psu.set_voltage(1, 5.0, false).unwrap();
+    assert_eq!(psu.get_voltage(1).unwrap(), 5.0);
+}
+
+#[test]
+fn set_and_verify_reports_mismatch() {
+    let mut psu = mock_psu(&["0", "1"]);
+    let result = psu.set_and_verify(|psu| psu.set_voltage_range(1, 2), |psu| psu.get_voltage_range(1), 2);
+    match result {
+        Err(MxError::ConfirmationMismatch { expected, actual, .. }) => {
+            assert_eq!(expected, "2");
+            assert_eq!(actual, "1");
+        }
+        other => panic!("expected confirmation mismatch, got {:?}", other),
+    }
+}
+
+#[test]
+fn command_hook_sees_commands_and_replies() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<MxSeries>();
+
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let mut psu = mock_psu(&["5.000V"]);
+    let log = Arc::clone(&seen);
+    psu.set_command_hook(Some(Box::new(move |command: &str, outcome: &Result<String, MxError>, elapsed: Duration| {
+        assert!(elapsed < Duration::from_secs(1));
+        log.lock().unwrap().push((command.to_string(), outcome.as_ref().ok().cloned()));
+    })));
+    psu.get_voltage(1).unwrap();
+    assert_eq!(*seen.lock().unwrap(), vec![("V1O?".to_string(), Some("5.000V".to_string()))]);
+}
+
+#[test]
+fn negative_or_nan_setpoints_are_rejected_before_sending() {
+    let mut psu = mock_psu(&[]);
+    assert!(matches!(psu.set_voltage(1, -1.0, false), Err(MxError::InvalidParameter(_))));
+    assert!(matches!(psu.set_current_limit(1, f32::NAN), Err(MxError::InvalidParameter(_))));
+    assert!(matches!(psu.set_over_voltage_protection(1, true, Some(f32::INFINITY)), Err(MxError::InvalidParameter(_))));
+    assert!(written(&mut psu).is_empty());
+}
+
+#[test]
+fn set_protection_sends_one_compound_command() {
+    let mut psu = mock_psu(&["0"]);
+    psu.set_protection(2, Some(13.2), None).unwrap();
+    assert_eq!(
+        written(&mut psu),
+        vec![b"OVP2 ON;OVP2 13.200;OCP2 OFF\n".to_vec(), b"*ESR?\n".to_vec()]
+    );
+}
+
+#[test]
+fn command_log_keeps_the_most_recent_commands() {
+    let mut psu = mock_psu(&["0", "5.000V"]);
+    psu.set_command_log_capacity(2);
+    psu.set_voltage(1, 5.0, false).unwrap();
+    psu.get_voltage(1).unwrap();
+    let log = psu.command_log();
+    let commands: Vec<_> = log.iter().map(|entry| entry.command.as_str()).collect();
+    assert_eq!(commands, ["*ESR?", "V1O?"]);
+    assert_eq!(log[1].outcome, Ok("5.000V".to_string()));
+}
+
+#[test]
+fn drain_errors_reads_until_no_error() {
+    let mut psu = mock_psu(&["102", "100", "0"]);
+    let errors = psu.drain_errors().unwrap();
+    let codes: Vec<i32> = errors.iter().map(|(code, _, _)| *code).collect();
+    assert_eq!(codes, [102, 100]);
+    assert_eq!(errors[0].1, "RecallError");
+}
+
+#[test]
+fn configure_multi_on_reports_each_channel() {
+    let mut psu = mock_psu(&["32", "0"]);
+    let options = HashMap::from([(2, MultiOperationConfig::Action(false)), (1, MultiOperationConfig::Action(true))]);
+    let results = psu.configure_multi_on(options);
+    assert_eq!(results.len(), 2);
+    assert!(matches!(results[0], (1, Err(MxError::CommandError(_)))));
+    assert!(matches!(results[1], (2, Ok(()))));
+}
+
+#[test]
+fn describe_range_uses_the_model_table() {
+    let mut psu = mock_psu(&["THURLBY THANDAR,MX180TP,123456,1.00-1.00-1.00", "4"]);
+    let range = psu.describe_range(1).unwrap();
+    assert_eq!((range.index, range.max_voltage, range.max_current), (4, 120.0, 3.0));
+}
+
+#[test]
+fn turn_on_multi_names_the_failing_channel() {
+    let mut psu = mock_psu(&["0", "32"]);
+    let options = HashMap::from([(1, MultiOperationConfig::Action(true)), (3, MultiOperationConfig::Action(true))]);
+    match psu.turn_on_multi(Some(options)) {
+        Err(MxError::MultiOp { channel: 3, source }) => assert!(matches!(*source, MxError::CommandError(_))),
+        other => panic!("unexpected result: {:?}", other),
+    }
+    assert!(!written(&mut psu).contains(&b"OPALL 1\n".to_vec()));
+}
+
+#[test]
+fn output_off_reason_tells_trips_from_manual_off() {
+    let mut psu = mock_psu(&["0", "8", "0", "0", "1"]);
+    assert_eq!(psu.output_off_reason(1).unwrap(), OffReason::TrippedOff);
+    assert_eq!(psu.output_off_reason(1).unwrap(), OffReason::ManualOff);
+    assert_eq!(psu.output_off_reason(1).unwrap(), OffReason::On);
+}
+
+#[test]
+fn retrying_connection_delegates_to_the_inner_connection() {
+    let mut mock = MockConnection::new();
+    mock.push_reply("5.000V");
+    let mut psu = MxSeries::from_connection(Box::new(RetryingConnection::new(mock, 2)));
+    assert_eq!(psu.connection_kind(), ConnectionKind::Mock);
+    assert_eq!(psu.get_voltage(1).unwrap(), 5.0);
+    assert!(matches!(psu.get_voltage(1), Err(MxError::Io(_))));
+    assert!(psu.connection_downcast_mut::<MockConnection>().is_some());
+}
+
+#[test]
+fn connections_need_only_the_original_methods() {
+    struct Minimal(Vec<String>);
+    impl Connection for Minimal {
+        fn write_command(&mut self, command: &str) -> Result<(), MxError> {
+            self.0.push(command.to_string());
+            Ok(())
+        }
+        fn read_response(&mut self) -> Result<String, MxError> {
+            Ok("0".to_string())
+        }
+        fn set_timeout(&mut self, _duration: Duration) -> Result<(), MxError> {
+            Ok(())
+        }
+    }
+    let mut psu = MxSeries::from_connection(Box::new(Minimal(Vec::new())));
+    psu.write_command_raw(b"*RST\n").unwrap();
+    assert!(matches!(psu.write_command_raw(b"*RST"), Err(MxError::UnsupportedFeature(_))));
+}
+
+#[test]
+fn close_keep_remote_does_not_send_local() {
+    let close = |keep_remote: bool| {
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let log = Arc::clone(&sent);
+        let mut psu = mock_psu(&[]);
+        psu.set_command_hook(Some(Box::new(move |command: &str, _: &Result<String, MxError>, _: Duration| {
+            log.lock().unwrap().push(command.to_string());
+        })));
+        if keep_remote { psu.close_keep_remote() } else { psu.close() }.unwrap();
+        let sent = sent.lock().unwrap().clone();
+        sent
+    };
+    assert_eq!(close(false), ["LOCAL"]);
+    assert!(close(true).is_empty());
+}
+
+#[test]
+fn reset_sends_opc_query_once_and_waits_for_its_reply() {
+    let mut psu = mock_psu(&["", "", "1"]);
+    psu.reset().unwrap();
+    assert_eq!(written(&mut psu), vec![b"*RST\n".to_vec(), b"*OPC?\n".to_vec()]);
+}
+
+#[test]
+fn status_query_retries_read_the_late_reply_without_resending() {
+    let mut psu = mock_psu(&["", "0"]);
+    psu.set_retry_policy(RetryPolicy::new(0).status_query_retries(1));
+    psu.set_voltage(1, 5.0, false).unwrap();
+    assert_eq!(written(&mut psu), vec![b"V1 5.000\n".to_vec(), b"*ESR?\n".to_vec()]);
+}
+
+#[test]
+fn emergency_off_sends_one_command_and_reports_zeroing_errors() {
+    let mut psu = mock_psu(&["0"]);
+    psu.emergency_off().unwrap();
+    assert_eq!(written(&mut psu), vec![b"OPALL 0;V1 0;V2 0;V3 0;V4 0\n".to_vec(), b"*ESR?\n".to_vec()]);
+
+    // A V clause is rejected with execution error 103, as with tracking enabled.
+    let mut psu = mock_psu(&["16", "103"]);
+    assert!(matches!(psu.emergency_off(), Err(MxError::ExecutionError { code: 103, .. })));
+}
+
+#[test]
+fn quantize_uses_the_last_known_range_without_io() {
+    let mut psu = mock_psu(&["THURLBY THANDAR,MX180TP,123456,1.00-1.00-1.00", "4"]);
+    psu.identify().unwrap();
+    // Range unknown: the 15V range covers 12.347V, with 1mV resolution.
+    assert!((psu.quantize_voltage(1, 12.347) - 12.347).abs() < 1e-4);
+    assert_eq!(psu.get_voltage_range(1).unwrap(), 4);
+    let written_before = written(&mut psu).len();
+    let quantized = psu.quantize_voltage(1, 12.347);
+    assert!((quantized - 12.35).abs() < 1e-4, "{}", quantized);
+    assert_eq!(written(&mut psu).len(), written_before);
+}
+
+#[test]
+fn echo_mode_discards_the_echo_of_raw_writes() {
+    let mut psu = mock_psu(&["*CLS", "V1O?", "5.000V"]);
+    psu.set_echo_mode(true);
+    psu.write_command_raw(b"*CLS\n").unwrap();
+    assert_eq!(psu.get_voltage(1).unwrap(), 5.0);
+}
+
+#[test]
+fn query_lines_logs_the_replies_joined_with_semicolons() {
+    let mut psu = mock_psu(&["V1 5.000", "V2 3.000"]);
+    psu.set_command_log_capacity(1);
+    let lines = psu.query_lines("V1?;V2?", 2).unwrap();
+    assert_eq!(lines, ["V1 5.000", "V2 3.000"]);
+    assert_eq!(psu.command_log()[0].outcome, Ok("V1 5.000;V2 3.000".to_string()));
+}
+
+#[test]
+fn retrying_connection_reads_again_after_a_timeout_but_never_rewrites() {
+    struct Flaky {
+        writes: Vec<String>,
+        write_timeouts: u32,
+        read_timeouts: u32,
+    }
+    impl Connection for Flaky {
+        fn write_command(&mut self, command: &str) -> Result<(), MxError> {
+            self.writes.push(command.to_string());
+            if self.write_timeouts > 0 {
+                self.write_timeouts -= 1;
+                return Err(std::io::Error::from(std::io::ErrorKind::TimedOut).into());
+            }
+            Ok(())
+        }
+        fn read_response(&mut self) -> Result<String, MxError> {
+            if self.read_timeouts > 0 {
+                self.read_timeouts -= 1;
+                return Err(std::io::Error::from(std::io::ErrorKind::TimedOut).into());
+            }
+            Ok("5.000V".to_string())
+        }
+        fn set_timeout(&mut self, _duration: Duration) -> Result<(), MxError> {
+            Ok(())
+        }
+    }
+    let mut flaky = RetryingConnection::new(Flaky { writes: Vec::new(), write_timeouts: 1, read_timeouts: 1 }, 2);
+    assert!(matches!(flaky.write_command("V1O?"), Err(MxError::Io(_))));
+    assert_eq!(flaky.inner_mut().writes, ["V1O?"]);
+    assert_eq!(flaky.query("V1O?").unwrap(), "5.000V");
+
+    let mut mock = MockConnection::new();
+    mock.push_reply("");
+    mock.push_reply("5.000V");
+    assert_eq!(RetryingConnection::new(mock, 1).read_response().unwrap(), "5.000V");
+}
+
+#[test]
+fn query_lines_reports_a_rejected_query_from_the_status_register() {
+    // A serial read returns an empty line on timeout; ESR bit 5 flags the command error.
+    let mut psu = mock_psu(&["", "", "32"]);
+    assert!(matches!(psu.query_lines("OVP1?;OCP1?", 2), Err(MxError::CommandError(_))));
+    assert_eq!(written(&mut psu).last().unwrap(), b"*ESR?\n");
+}
+
+#[test]
+fn protection_limits_fall_back_when_compound_queries_are_rejected() {
+    let mut psu = mock_psu(&["", "", "32", "OVP1 30.50", "OCP1 OFF"]);
+    assert_eq!(psu.get_protection_limits(1).unwrap(), (Some(30.5), None));
+    assert_eq!(written(&mut psu)[2..], [b"OVP1?\n".to_vec(), b"OCP1?\n".to_vec()]);
+}
+
+#[test]
+fn auto_range_rejects_an_out_of_range_index_reply() {
+    let mut psu = mock_psu(&["THURLBY THANDAR,MX180TP,123456,1.00-1.00-1.00", "-2147483648"]);
+    assert!(matches!(psu.set_current_limit_auto_range(1, 1.0), Err(MxError::Parse(_))));
+}
+
+#[test]
+fn protection_settings_fall_back_to_single_queries() {
+    let mut psu = mock_psu(&[
+        "THURLBY THANDAR,MX100TP,123456,1.00-1.00-1.00",
+        "", "", "", "", "", "", "32",
+        "OVP1 30.50", "OCP1 OFF", "OVP2 OFF", "OCP2 1.000", "OVP3 OFF", "OCP3 OFF",
+    ]);
+    let settings = psu.protection_settings().unwrap();
+    assert_eq!(settings[0], (1, Some(30.5), None));
+    assert_eq!(settings[1], (2, None, Some(1.0)));
+    assert!(!written(&mut psu).contains(&b"OVP1?;OCP1?\n".to_vec()));
+}
+
+#[test]
+fn describe_range_rejects_an_out_of_range_index_reply() {
+    let mut psu = mock_psu(&["THURLBY THANDAR,MX180TP,123456,1.00-1.00-1.00", "-2147483648"]);
+    assert!(matches!(psu.describe_range(1), Err(MxError::Parse(_))));
+}
@@ -0,0 +1,30 @@
+use mxpsu::error::MxError;
+use mxpsu::{MeterAveraging, MultiActionType};
+
+#[test]
+fn meter_averaging_parses_every_variant() {
+    let cases = [
+        ("ON", MeterAveraging::On),
+        ("off", MeterAveraging::Off),
+        ("Low", MeterAveraging::Low),
+        ("MED", MeterAveraging::Med),
+        (" high ", MeterAveraging::High),
+    ];
+    for (text, expected) in cases {
+        assert_eq!(text.parse::<MeterAveraging>().unwrap(), expected);
+    }
+    assert!(matches!("MEDIUM".parse::<MeterAveraging>(), Err(MxError::Parse(_))));
+}
+
+#[test]
+fn multi_action_type_parses_every_variant() {
+    let cases = [
+        ("QUICK", MultiActionType::Quick),
+        ("never", MultiActionType::Never),
+        ("Delay", MultiActionType::Delay),
+    ];
+    for (text, expected) in cases {
+        assert_eq!(text.parse::<MultiActionType>().unwrap(), expected);
+    }
+    assert!(matches!("SLOW".parse::<MultiActionType>(), Err(MxError::Parse(_))));
+}
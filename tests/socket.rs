@@ -0,0 +1,184 @@
+mod common;
+
+use common::ScriptedServer;
+use mxpsu::error::MxError;
+use mxpsu::{MxSeries, RetryPolicy};
+
+const IDN: &str = "THURLBY THANDAR,MX100TP,123456,1.00-1.00-1.00";
+
+#[test]
+fn get_voltage_parses_reply() {
+    let server = ScriptedServer::start(vec![("V1O?", Some("5.000V"))]);
+    let mut psu = MxSeries::connect_socket(&server.address).unwrap();
+    assert_eq!(psu.get_voltage(1).unwrap(), 5.0);
+    drop(psu);
+    server.finish();
+}
+
+#[test]
+fn set_voltage_checks_esr() {
+    let server = ScriptedServer::start(vec![("V1 5.000", None), ("*ESR?", Some("0"))]);
+    let mut psu = MxSeries::connect_socket(&server.address).unwrap();
+    psu.set_voltage(1, 5.0, false).unwrap();
+    drop(psu);
+    server.finish();
+}
+
+#[test]
+fn execution_error_is_decoded() {
+    let server = ScriptedServer::start(vec![
+        ("V1 99.000", None),
+        ("*ESR?", Some("16")),
+        ("EER?", Some("100")),
+    ]);
+    let mut psu = MxSeries::connect_socket(&server.address).unwrap();
+    match psu.set_voltage(1, 99.0, false) {
+        Err(MxError::ExecutionError { code, error_type, .. }) => {
+            assert_eq!(code, 100);
+            assert_eq!(error_type, "NumericError");
+        }
+        other => panic!("expected execution error, got {:?}", other),
+    }
+    drop(psu);
+    server.finish();
+}
+
+#[test]
+fn reset_trips_verified_reports_recovered_channels() {
+    let server = ScriptedServer::start(vec![
+        ("*IDN?", Some(IDN)),
+        ("LSR1?", Some("8")),
+        ("LSR2?", Some("0")),
+        ("LSR3?", Some("0")),
+        ("TRIPRST", None),
+        ("*ESR?", Some("0")),
+        ("LSR1?", Some("0")),
+    ]);
+    let mut psu = MxSeries::connect_socket(&server.address).unwrap();
+    assert_eq!(psu.reset_trips_verified().unwrap(), vec![1]);
+    drop(psu);
+    server.finish();
+}
+
+#[test]
+fn reset_trips_verified_fails_when_trip_persists() {
+    let server = ScriptedServer::start(vec![
+        ("*IDN?", Some(IDN)),
+        ("LSR1?", Some("0")),
+        ("LSR2?", Some("4")),
+        ("LSR3?", Some("0")),
+        ("TRIPRST", None),
+        ("*ESR?", Some("0")),
+        ("LSR2?", Some("4")),
+    ]);
+    let mut psu = MxSeries::connect_socket(&server.address).unwrap();
+    assert!(matches!(psu.reset_trips_verified(), Err(MxError::TripPersists { channel: 2 })));
+    drop(psu);
+    server.finish();
+}
+
+#[test]
+fn channel_status_line_uses_one_batched_query() {
+    let server = ScriptedServer::start(vec![(
+        "V2?;V2O?;I2?;I2O?;LSR2?",
+        Some("V2 12.000\n11.998V\nI2 1.000\n1.000A\n2"),
+    )]);
+    let mut psu = MxSeries::connect_socket(&server.address).unwrap();
+    let status = psu.channel_status_line(2).unwrap();
+    assert_eq!(status.voltage_setpoint, 12.0);
+    assert_eq!(status.voltage, 11.998);
+    assert_eq!(status.current, 1.0);
+    assert_eq!(status.mode, mxpsu::OperatingMode::ConstantCurrent);
+    assert!(!status.trip.is_tripped());
+    drop(psu);
+    server.finish();
+}
+
+#[test]
+fn over_current_protection_readback_mismatch_is_reported() {
+    let server = ScriptedServer::start(vec![
+        ("OCP1 ON", None),
+        ("*ESR?", Some("0")),
+        ("OCP1 1.500", None),
+        ("*ESR?", Some("0")),
+        ("OCP1?", Some("OCP1 OFF")),
+    ]);
+    let mut psu = MxSeries::connect_socket(&server.address).unwrap();
+    match psu.set_over_current_protection(1, true, Some(1.5)) {
+        Err(MxError::ConfirmationMismatch { setting, actual, .. }) => {
+            assert_eq!(setting, "OCP1");
+            assert_eq!(actual, "OFF");
+        }
+        other => panic!("expected confirmation mismatch, got {:?}", other),
+    }
+    drop(psu);
+    server.finish();
+}
+
+#[test]
+fn over_voltage_protection_rejected_value_is_reported() {
+    let server = ScriptedServer::start(vec![
+        ("OVP2 ON", None),
+        ("*ESR?", Some("0")),
+        ("OVP2 99.000", None),
+        ("*ESR?", Some("16")),
+        ("EER?", Some("100")),
+    ]);
+    let mut psu = MxSeries::connect_socket(&server.address).unwrap();
+    match psu.set_over_voltage_protection(2, true, Some(99.0)) {
+        Err(MxError::ProtectionValueRejected { setting, source }) => {
+            assert_eq!(setting, "OVP2");
+            assert!(matches!(*source, MxError::ExecutionError { code: 100, .. }));
+        }
+        other => panic!("expected rejected protection value, got {:?}", other),
+    }
+    drop(psu);
+    server.finish();
+}
+
+#[test]
+fn measure_all_batches_every_channel() {
+    let server = ScriptedServer::start(vec![
+        ("*IDN?", Some(IDN)),
+        (
+            "V1O?;I1O?;V2O?;I2O?;V3O?;I3O?",
+            Some("5.000V\n0.100A\n12.000V\n0.200A\n3.300V\n0.300A"),
+        ),
+    ]);
+    let mut psu = MxSeries::connect_socket(&server.address).unwrap();
+    let measurements = psu.measure_all().unwrap();
+    assert_eq!(measurements.len(), 3);
+    assert_eq!(measurements[1].to_string(), "CH2: 12.000V / 0.200A");
+    drop(psu);
+    server.finish();
+}
+
+#[test]
+fn command_errors_are_retried_when_enabled() {
+    let server = ScriptedServer::start(vec![
+        ("V1 5.000", None),
+        ("*ESR?", Some("32")),
+        ("V1 5.000", None),
+        ("*ESR?", Some("0")),
+    ]);
+    let mut psu = MxSeries::connect_socket(&server.address).unwrap();
+    psu.set_retry_policy(RetryPolicy::new(1).retry_command_errors(true));
+    psu.set_voltage(1, 5.0, false).unwrap();
+    drop(psu);
+    server.finish();
+}
+
+#[test]
+fn carriage_return_terminator_is_detected() {
+    let server = ScriptedServer::start_with_terminator(
+        vec![("V1O?", Some("5.000V")), ("I1O?", Some("0.100A")), ("V2O?", Some("3.300V"))],
+        "\r",
+    );
+    let mut psu = MxSeries::connect_socket(&server.address).unwrap();
+    psu.detect_read_terminator().unwrap();
+    assert_eq!(psu.get_voltage(1).unwrap(), 5.0);
+    assert_eq!(psu.get_current(1).unwrap(), 0.1);
+    assert_eq!(psu.get_voltage(2).unwrap(), 3.3);
+    drop(psu);
+    server.finish();
+}